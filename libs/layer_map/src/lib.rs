@@ -1,21 +1,27 @@
 use std::{
     cell::{RefCell, RefMut},
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
     future::Future,
     io::Read,
     marker::PhantomData,
     ops::Deref,
     pin::Pin,
-    sync::{Arc, Mutex, RwLock},
+    sync::{
+        atomic::{AtomicBool, AtomicPtr, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    time::Duration,
 };
 
 use utils::seqwait::{self, Advance, SeqWait, Wait};
 
 pub trait Types {
-    type Key: Copy;
+    type Key: Copy + Ord;
     type Lsn: Ord + Copy;
     type LsnCounter: seqwait::MonotonicCounter<Self::Lsn> + Copy;
     type DeltaRecord;
-    type HistoricLayer;
+    type HistoricLayer: HistoricLayerCursor<Self>;
     type InMemoryLayer: InMemoryLayer<Types = Self>;
     type HistoricStuff: HistoricStuff<Types = Self>;
 }
@@ -34,12 +40,26 @@ pub trait InMemoryLayer: std::fmt::Debug + Default + Clone {
         lsn: <Self::Types as Types>::Lsn,
         delta: <Self::Types as Types>::DeltaRecord,
     ) -> Result<(), (<Self::Types as Types>::DeltaRecord, InMemoryLayerPutError)>;
+    /// Every record held for `key` at or below `lsn`, newest first, paired with the `Lsn` it
+    /// was written at so callers (notably [`MergeIterator`]) can place it in a global
+    /// `(Key, Lsn)` order instead of just the per-layer order this method happens to return.
     fn get(
         &self,
         key: <Self::Types as Types>::Key,
         lsn: <Self::Types as Types>::Lsn,
-    ) -> Vec<<Self::Types as Types>::DeltaRecord>;
+    ) -> Vec<(<Self::Types as Types>::Lsn, <Self::Types as Types>::DeltaRecord)>;
     fn freeze(&mut self);
+    /// A cheap, approximate measure of how much this layer currently holds -- entry count,
+    /// byte size, whatever's convenient for the implementation. Used only by
+    /// [`FlusherWriteHandle::put`] to decide when to wake [`ReadWriter::spawn_flusher`]'s
+    /// background task early, before the layer actually fills up and returns `LayerFull`; a
+    /// direct [`ReadWriter::put`] never consults it. The default of `0` means "never wake the
+    /// flusher early" (it still flushes on its timer), so every existing
+    /// [`InMemoryLayer`] impl keeps compiling unchanged; an impl that wants proactive flushing
+    /// to kick in should override this.
+    fn approximate_len(&self) -> usize {
+        0
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -56,12 +76,980 @@ pub trait HistoricStuff {
     fn make_historic(&self, inmem: <Self::Types as Types>::InMemoryLayer) -> Self;
 }
 
+/// A single position within one layer's contribution to a [`MergeIterator`] scan: the next
+/// `(Key, Lsn, DeltaRecord)` it has to offer, if any.
+///
+/// Implementations are free to be as lazy as they like: a cursor backed by an on-disk layer
+/// (see the persistent layer format) should only read the block it needs, not the whole
+/// layer, and a cursor whose `peek()` never returns `Some` should never touch its backing
+/// storage at all.
+pub trait MergeCursor<T: Types> {
+    /// The `(key, lsn)` of the next item this cursor would yield, without consuming it.
+    fn peek(&self) -> Option<(T::Key, T::Lsn)>;
+    /// Consume and return the current head, advancing the cursor past it.
+    fn advance(&mut self) -> Option<(T::Key, T::Lsn, T::DeltaRecord)>;
+}
+
+/// The simplest possible [`MergeCursor`]: a pre-materialized, already-ordered run of records.
+/// Used to adapt [`InMemoryLayer::get`]'s eager `Vec` into the cursor interface until there's
+/// a genuinely lazy in-memory representation (see the skip-list in-memory layer) to back it.
+pub struct VecMergeCursor<T: Types> {
+    items: VecDeque<(T::Key, T::Lsn, T::DeltaRecord)>,
+}
+
+impl<T: Types> VecMergeCursor<T> {
+    pub fn new(items: Vec<(T::Key, T::Lsn, T::DeltaRecord)>) -> Self {
+        VecMergeCursor {
+            items: items.into(),
+        }
+    }
+}
+
+impl<T: Types> MergeCursor<T> for VecMergeCursor<T> {
+    fn peek(&self) -> Option<(T::Key, T::Lsn)> {
+        self.items.front().map(|(key, lsn, _)| (*key, *lsn))
+    }
+
+    fn advance(&mut self) -> Option<(T::Key, T::Lsn, T::DeltaRecord)> {
+        self.items.pop_front()
+    }
+}
+
+/// Turns a historic layer into a [`MergeCursor`] for a given `(key, lsn)` lookup, without the
+/// [`MergeIterator`] needing to know whether that layer lives on disk, in a test's in-memory
+/// `BTreeMap`, or anywhere else.
+pub trait HistoricLayerCursor<T: Types + ?Sized> {
+    fn cursor(&self, key: T::Key, lsn: T::Lsn) -> Box<dyn MergeCursor<T> + Send>;
+}
+
+/// One layer's current head inside the [`MergeIterator`]'s heap, ordered so the heap pops
+/// entries key-ascending (for an eventual range scan) and, for equal keys, Lsn-descending
+/// (newest record first, which is what a point reconstruct wants: walk backwards from the
+/// most recent record until a full-page image is found). `precedence` breaks ties between
+/// layers that somehow hold a record for the exact same `(key, lsn)` — lower wins, and
+/// cursors are always built with the in-memory layer at precedence 0, ahead of every historic
+/// layer.
+struct HeapEntry<T: Types> {
+    key: T::Key,
+    lsn: T::Lsn,
+    precedence: usize,
+    cursor: usize,
+}
+
+impl<T: Types> HeapEntry<T> {
+    fn sort_key(&self) -> (T::Key, Reverse<T::Lsn>, usize) {
+        (self.key, Reverse(self.lsn), self.precedence)
+    }
+}
+
+impl<T: Types> PartialEq for HeapEntry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.sort_key() == other.sort_key()
+    }
+}
+
+impl<T: Types> Eq for HeapEntry<T> {}
+
+impl<T: Types> PartialOrd for HeapEntry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Types> Ord for HeapEntry<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // BinaryHeap is a max-heap; reverse the comparison so the smallest sort_key() (i.e.
+        // the globally next record) pops first.
+        other.sort_key().cmp(&self.sort_key())
+    }
+}
+
+/// How a [`MergeCallback`] wants the next record classified, so [`MergeIterator::merge`] can
+/// collapse WAL-style deltas or cut a scan short instead of always appending a new entry.
+pub enum MergeResult<T: Types> {
+    /// Append as a new entry in the merged output.
+    Insert(T::DeltaRecord),
+    /// Replace the previously emitted entry for this `(key, lsn)` if there is one, otherwise
+    /// insert fresh (e.g. a tombstone superseding an earlier partial record).
+    ReplaceOrInsert(T::DeltaRecord),
+    /// Logically belongs folded into the previously emitted entry for this key rather than as
+    /// a standalone one (e.g. chained WAL deltas). See the doc comment on
+    /// [`MergeIterator::merge`] for the current limitation here.
+    MergeInto(T::DeltaRecord),
+}
+
+/// Callback hook for [`MergeIterator::merge`]: inspects each record as it comes off the heap
+/// and decides how it should land in the merged output.
+pub trait MergeCallback<T: Types> {
+    fn classify(&mut self, key: T::Key, lsn: T::Lsn, delta: T::DeltaRecord) -> MergeResult<T>;
+}
+
+/// Streaming k-way merge over every layer relevant to a read — the in-memory layer plus each
+/// historic layer on the reconstruct path — producing records in global `(Key, Lsn)` order
+/// (see [`HeapEntry`]) without requiring any single layer to materialize its whole
+/// contribution up front.
+///
+/// Layers already known not to overlap (see the `assert!` in `make_historic`) could in
+/// principle share one cursor instead of one each, but this first cut keeps it simple: one
+/// cursor per layer, seeded lazily so a layer whose `peek()` comes back empty never enters the
+/// heap and is never consulted again.
+pub struct MergeIterator<T: Types> {
+    cursors: Vec<Box<dyn MergeCursor<T> + Send>>,
+    heap: BinaryHeap<HeapEntry<T>>,
+}
+
+impl<T: Types> MergeIterator<T> {
+    pub fn new(cursors: Vec<Box<dyn MergeCursor<T> + Send>>) -> Self {
+        let mut heap = BinaryHeap::with_capacity(cursors.len());
+        for (idx, cursor) in cursors.iter().enumerate() {
+            if let Some((key, lsn)) = cursor.peek() {
+                heap.push(HeapEntry {
+                    key,
+                    lsn,
+                    precedence: idx,
+                    cursor: idx,
+                });
+            }
+        }
+        MergeIterator { cursors, heap }
+    }
+
+    /// Pop the globally next `(key, lsn, delta)`, re-seeding the heap with that cursor's new
+    /// head if it has one.
+    pub fn next(&mut self) -> Option<(T::Key, T::Lsn, T::DeltaRecord)> {
+        let entry = self.heap.pop()?;
+        let cursor = &mut self.cursors[entry.cursor];
+        let item = cursor
+            .advance()
+            .expect("a cursor on the heap always has a peeked head to advance past");
+        if let Some((key, lsn)) = cursor.peek() {
+            heap_push(&mut self.heap, key, lsn, entry.precedence, entry.cursor);
+        }
+        Some(item)
+    }
+
+    /// Drain the merge through `callback`, collapsing/cutting short per its classification.
+    ///
+    /// `MergeInto` currently has no `DeltaRecord`-specific combine operation to actually fold
+    /// records together — `T::DeltaRecord` is an opaque associated type here — so it's emitted
+    /// the same as `Insert` for now; real WAL delta collapsing needs either a `Combine` bound
+    /// on `T::DeltaRecord` or for layers to pre-combine before handing records to the cursor.
+    pub fn merge(mut self, mut callback: impl MergeCallback<T>) -> Vec<(T::Key, T::Lsn, T::DeltaRecord)> {
+        let mut out: Vec<(T::Key, T::Lsn, T::DeltaRecord)> = Vec::new();
+        while let Some((key, lsn, delta)) = self.next() {
+            match callback.classify(key, lsn, delta) {
+                MergeResult::Insert(delta) | MergeResult::MergeInto(delta) => {
+                    out.push((key, lsn, delta));
+                }
+                MergeResult::ReplaceOrInsert(delta) => match out.last_mut() {
+                    Some(last) if last.0 == key && last.1 == lsn => *last = (key, lsn, delta),
+                    _ => out.push((key, lsn, delta)),
+                },
+            }
+        }
+        out
+    }
+}
+
+fn heap_push<T: Types>(
+    heap: &mut BinaryHeap<HeapEntry<T>>,
+    key: T::Key,
+    lsn: T::Lsn,
+    precedence: usize,
+    cursor: usize,
+) {
+    heap.push(HeapEntry {
+        key,
+        lsn,
+        precedence,
+        cursor,
+    });
+}
+
+/// Latest on-disk format version a [`SimplePersistentLayerWriter`] produces. Bumped whenever
+/// the block/index/footer layout changes; [`SimplePersistentLayerReader::open`] refuses to
+/// open a layer whose footer carries any other version, older or newer.
+pub const LAYER_FORMAT_LATEST_VERSION: u32 = 1;
+
+/// Read side of the byte source a [`SimplePersistentLayerReader`] reads from -- a file, an
+/// object-storage blob, or (see the test impl) a `Vec<u8>` held in memory. Modeled as a
+/// `pread`-style positioned read rather than `Read + Seek` so concurrent cursors over the same
+/// handle don't need to coordinate a shared position.
+pub trait ReadObjectHandle {
+    /// Read as many bytes as are available starting at `offset` into `buf`, returning how many
+    /// were read (`0` only at end-of-object). Short reads are expected and handled by
+    /// [`read_exact_at`], not an error on their own.
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize>;
+}
+
+/// Retry [`ReadObjectHandle::read_at`] until `buf` is completely filled, since a single call is
+/// allowed to return short.
+fn read_exact_at(handle: &impl ReadObjectHandle, mut offset: u64, mut buf: &mut [u8]) -> std::io::Result<()> {
+    while !buf.is_empty() {
+        let n = handle.read_at(offset, buf)?;
+        if n == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "short read from ReadObjectHandle",
+            ));
+        }
+        offset += n as u64;
+        buf = &mut buf[n..];
+    }
+    Ok(())
+}
+
+/// A `Vec<u8>`-backed [`ReadObjectHandle`], useful for tests and for holding a just-written
+/// layer before it's actually uploaded anywhere.
+pub struct InMemoryObjectHandle(pub Vec<u8>);
+
+impl ReadObjectHandle for InMemoryObjectHandle {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+        let offset = offset as usize;
+        if offset >= self.0.len() {
+            return Ok(0);
+        }
+        let n = buf.len().min(self.0.len() - offset);
+        buf[..n].copy_from_slice(&self.0[offset..offset + n]);
+        Ok(n)
+    }
+}
+
+/// Encodes/decodes one `(Key, Lsn, DeltaRecord)` entry (and, separately, a bare `Key` for the
+/// block index) to and from the byte stream a [`SimplePersistentLayerWriter`] writes and a
+/// [`SimplePersistentLayerReader`] reads back.
+///
+/// Kept as a standalone trait parameterizing the writer/reader, rather than another
+/// associated type on [`Types`], so a [`Types`] impl that never persists anything (like the
+/// test suite's pure in-memory `HistoricStuff`) isn't forced to supply a codec it has no use
+/// for.
+pub trait EntryCodec<T: Types> {
+    /// Append the encoded form of `(key, lsn, delta)` to `out`.
+    fn encode_entry(key: T::Key, lsn: T::Lsn, delta: &T::DeltaRecord, out: &mut Vec<u8>);
+    /// Decode one entry from the front of `buf`, returning it along with the number of bytes
+    /// consumed.
+    fn decode_entry(buf: &[u8]) -> (T::Key, T::Lsn, T::DeltaRecord, usize);
+    /// Append the encoded form of a bare `key` to `out`, for the block index.
+    fn encode_key(key: T::Key, out: &mut Vec<u8>);
+    /// Decode one key from the front of `buf`, returning it along with the number of bytes
+    /// consumed.
+    fn decode_key(buf: &[u8]) -> (T::Key, usize);
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum OpenPersistentLayerError {
+    #[error("unsupported persistent layer format version {found}, expected {expected}")]
+    UnsupportedVersion { found: u32, expected: u32 },
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// One block's worth of encoded entries, as recorded in the trailing sparse index: the
+/// block's first key (so a lookup can binary-search for the block that might contain a given
+/// key without reading anything but the index) plus where the block lives in the file.
+struct BlockIndexEntry<K> {
+    first_key: K,
+    offset: u64,
+    len: u32,
+}
+
+/// Streams `(Key, Lsn, DeltaRecord)` entries -- which the caller must supply already sorted in
+/// ascending `(Key, Lsn)` order, the same invariant `make_historic` already relies on -- into
+/// blocks that are cut once they reach `block_size`, at the next key boundary (see
+/// [`Self::write_entry`]). `finish()` appends a trailing sparse index (one `(first_key, offset,
+/// len)` triple per block) and a fixed-size footer carrying the format version, entry count,
+/// block count, and the index's offset. Modeled on fxfs's `simple_persistent_layer` writer.
+pub struct SimplePersistentLayerWriter<T: Types, C: EntryCodec<T>> {
+    block_size: usize,
+    out: Vec<u8>,
+    current_block: Vec<u8>,
+    current_block_first_key: Option<T::Key>,
+    current_block_last_key: Option<T::Key>,
+    index: Vec<BlockIndexEntry<T::Key>>,
+    entry_count: u64,
+    _codec: PhantomData<(T, C)>,
+}
+
+impl<T: Types, C: EntryCodec<T>> SimplePersistentLayerWriter<T, C> {
+    pub fn new(block_size: usize) -> Self {
+        SimplePersistentLayerWriter {
+            block_size,
+            out: Vec::new(),
+            current_block: Vec::new(),
+            current_block_first_key: None,
+            current_block_last_key: None,
+            index: Vec::new(),
+            entry_count: 0,
+            _codec: PhantomData,
+        }
+    }
+
+    /// Append one entry. Entries must be supplied in ascending `(Key, Lsn)` order; like
+    /// `make_historic`'s "layers must not overlap" assert, that invariant isn't re-checked
+    /// here and is the caller's responsibility.
+    ///
+    /// The block-size threshold is only ever consulted at a key boundary: we check it right
+    /// before starting a *new* key's first entry, never in the middle of one key's run. A
+    /// reader locates a key's entries with a single binary search over blocks' `first_key`s,
+    /// so if a key's run were allowed to straddle a block boundary, the tail half would land
+    /// in a block whose `first_key` is that same key and the earlier half -- sitting in the
+    /// prior block -- would never be read back. Keeping a key's run in one block means a
+    /// block can grow past `block_size` when a single key has many entries, which is the
+    /// trade-off for correctness here.
+    pub fn write_entry(&mut self, key: T::Key, lsn: T::Lsn, delta: &T::DeltaRecord) {
+        let starting_new_key = self.current_block_last_key != Some(key);
+        if starting_new_key
+            && !self.current_block.is_empty()
+            && self.current_block.len() >= self.block_size
+        {
+            self.flush_block();
+        }
+        if self.current_block_first_key.is_none() {
+            self.current_block_first_key = Some(key);
+        }
+        C::encode_entry(key, lsn, delta, &mut self.current_block);
+        self.current_block_last_key = Some(key);
+        self.entry_count += 1;
+    }
+
+    fn flush_block(&mut self) {
+        if self.current_block.is_empty() {
+            return;
+        }
+        let first_key = self
+            .current_block_first_key
+            .take()
+            .expect("a non-empty block always has a first entry");
+        let offset = self.out.len() as u64;
+        let len = self.current_block.len() as u32;
+        self.out.extend_from_slice(&self.current_block);
+        self.current_block.clear();
+        self.index.push(BlockIndexEntry {
+            first_key,
+            offset,
+            len,
+        });
+    }
+
+    /// Finish writing: flush any partial last block, append the sparse index and footer, and
+    /// return the fully serialized layer.
+    pub fn finish(mut self) -> Vec<u8> {
+        self.flush_block();
+        let index_offset = self.out.len() as u64;
+        let block_count = self.index.len() as u32;
+        for block in &self.index {
+            C::encode_key(block.first_key, &mut self.out);
+            self.out.extend_from_slice(&block.offset.to_le_bytes());
+            self.out.extend_from_slice(&block.len.to_le_bytes());
+        }
+        self.out.extend_from_slice(&LAYER_FORMAT_LATEST_VERSION.to_le_bytes());
+        self.out.extend_from_slice(&self.entry_count.to_le_bytes());
+        self.out.extend_from_slice(&block_count.to_le_bytes());
+        self.out.extend_from_slice(&index_offset.to_le_bytes());
+        self.out
+    }
+}
+
+/// Fixed size of the footer [`SimplePersistentLayerWriter::finish`] appends: `version: u32` +
+/// `entry_count: u64` + `block_count: u32` + `index_offset: u64`.
+const PERSISTENT_LAYER_FOOTER_SIZE: u64 = 4 + 8 + 4 + 8;
+
+/// Reads back a layer written by [`SimplePersistentLayerWriter`]: opens via a
+/// [`ReadObjectHandle`], binary-searches the in-memory block index for the block that might
+/// contain a key, and reads only that block.
+pub struct SimplePersistentLayerReader<T: Types, C: EntryCodec<T>, H: ReadObjectHandle> {
+    handle: H,
+    index: Vec<BlockIndexEntry<T::Key>>,
+    entry_count: u64,
+    _codec: PhantomData<(T, C)>,
+}
+
+impl<T: Types, C: EntryCodec<T>, H: ReadObjectHandle> SimplePersistentLayerReader<T, C, H> {
+    /// Open a layer of `total_len` bytes backed by `handle`, validating the footer's format
+    /// version before trusting anything else in it.
+    pub fn open(handle: H, total_len: u64) -> Result<Self, OpenPersistentLayerError> {
+        let mut footer = [0u8; PERSISTENT_LAYER_FOOTER_SIZE as usize];
+        read_exact_at(&handle, total_len - PERSISTENT_LAYER_FOOTER_SIZE, &mut footer)?;
+
+        let version = u32::from_le_bytes(footer[0..4].try_into().unwrap());
+        if version != LAYER_FORMAT_LATEST_VERSION {
+            return Err(OpenPersistentLayerError::UnsupportedVersion {
+                found: version,
+                expected: LAYER_FORMAT_LATEST_VERSION,
+            });
+        }
+        let entry_count = u64::from_le_bytes(footer[4..12].try_into().unwrap());
+        let block_count = u32::from_le_bytes(footer[12..16].try_into().unwrap());
+        let index_offset = u64::from_le_bytes(footer[16..24].try_into().unwrap());
+
+        let index_len = (total_len - PERSISTENT_LAYER_FOOTER_SIZE - index_offset) as usize;
+        let mut index_buf = vec![0u8; index_len];
+        read_exact_at(&handle, index_offset, &mut index_buf)?;
+
+        let mut index = Vec::with_capacity(block_count as usize);
+        let mut pos = 0;
+        while pos < index_buf.len() {
+            let (first_key, consumed) = C::decode_key(&index_buf[pos..]);
+            pos += consumed;
+            let offset = u64::from_le_bytes(index_buf[pos..pos + 8].try_into().unwrap());
+            pos += 8;
+            let len = u32::from_le_bytes(index_buf[pos..pos + 4].try_into().unwrap());
+            pos += 4;
+            index.push(BlockIndexEntry {
+                first_key,
+                offset,
+                len,
+            });
+        }
+
+        Ok(SimplePersistentLayerReader {
+            handle,
+            index,
+            entry_count,
+            _codec: PhantomData,
+        })
+    }
+
+    pub fn entry_count(&self) -> u64 {
+        self.entry_count
+    }
+
+    /// Read and decode just the block that might contain `key`, via a binary search over the
+    /// in-memory index -- never the whole layer. This relies on [`SimplePersistentLayerWriter`]
+    /// never splitting one key's run of entries across two blocks, so a single block is always
+    /// enough: every block's `first_key` is distinct, and a key's entire run lives in whichever
+    /// block that key is the first (or a predecessor) of.
+    fn read_block_containing(&self, key: T::Key) -> std::io::Result<Vec<(T::Key, T::Lsn, T::DeltaRecord)>> {
+        let block_idx = match self.index.binary_search_by(|b| b.first_key.cmp(&key)) {
+            Ok(i) => i,
+            Err(0) => return Ok(Vec::new()), // key precedes every block's first key
+            Err(i) => i - 1,
+        };
+        let block = &self.index[block_idx];
+        let mut buf = vec![0u8; block.len as usize];
+        read_exact_at(&self.handle, block.offset, &mut buf)?;
+
+        let mut entries = Vec::new();
+        let mut pos = 0;
+        while pos < buf.len() {
+            let (k, l, d, consumed) = C::decode_entry(&buf[pos..]);
+            entries.push((k, l, d));
+            pos += consumed;
+        }
+        Ok(entries)
+    }
+
+    /// Build a cursor over every entry for `key` at or below `lsn`, newest first, compatible
+    /// with [`MergeIterator`].
+    pub fn cursor(&self, key: T::Key, lsn: T::Lsn) -> std::io::Result<VecMergeCursor<T>> {
+        let mut matching: Vec<_> = self
+            .read_block_containing(key)?
+            .into_iter()
+            .filter(|(k, l, _)| *k == key && *l <= lsn)
+            .collect();
+        matching.sort_by(|a, b| b.1.cmp(&a.1));
+        Ok(VecMergeCursor::new(matching))
+    }
+}
+
+/// Result of a [`LayerCache::get`] lookup.
+pub enum ObjectCacheResult<V> {
+    /// Already open and decoded; no need to redo the open.
+    Cached(Arc<V>),
+    /// Not present. The caller should open it itself and, typically, [`LayerCache::insert`] the
+    /// result (see [`LayerCache::get_or_open`], which does both in one call).
+    Miss,
+}
+
+/// Hit/miss counters for a [`LayerCache`], public so a caller can fold them into whatever
+/// metrics system it already has.
+#[derive(Debug, Default)]
+pub struct LayerCacheStats {
+    pub hits: std::sync::atomic::AtomicU64,
+    pub misses: std::sync::atomic::AtomicU64,
+}
+
+struct CacheEntry<V> {
+    value: Arc<V>,
+    size: usize,
+}
+
+struct LruState<K, V> {
+    /// Least-recently-used key at the front, most-recently-used at the back.
+    order: VecDeque<K>,
+    entries: HashMap<K, CacheEntry<V>>,
+    total_bytes: usize,
+}
+
+/// An LRU cache sitting in front of expensive-to-open, cheap-to-share layer opens -- modeled on
+/// fxfs's `lsm_tree::cache`. Entries are evicted by approximate decoded size rather than entry
+/// count, so a cache holding a few huge layers and one holding many small ones are weighed the
+/// same way against `budget_bytes`.
+///
+/// Entries are `Arc`-wrapped so every concurrent caller that hits the cache shares the exact
+/// same decoded object (e.g. the same already-parsed [`SimplePersistentLayerReader`] block
+/// index) instead of each re-opening and re-parsing its own copy. This also makes eviction safe
+/// with respect to in-flight reads: evicting an entry only ever drops *the cache's* `Arc`
+/// reference, never the value itself, so a caller still holding a clone of that `Arc` (e.g. a
+/// live [`ReconstructWork`] built from a cursor over it) keeps it alive exactly as long as it
+/// needs to, regardless of what the cache has since done with its own reference.
+pub struct LayerCache<K, V> {
+    budget_bytes: usize,
+    state: Mutex<LruState<K, V>>,
+    stats: LayerCacheStats,
+}
+
+impl<K: std::hash::Hash + Eq + Clone, V> LayerCache<K, V> {
+    pub fn new(budget_bytes: usize) -> Self {
+        LayerCache {
+            budget_bytes,
+            state: Mutex::new(LruState {
+                order: VecDeque::new(),
+                entries: HashMap::new(),
+                total_bytes: 0,
+            }),
+            stats: LayerCacheStats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> &LayerCacheStats {
+        &self.stats
+    }
+
+    pub fn total_bytes(&self) -> usize {
+        self.state.lock().unwrap().total_bytes
+    }
+
+    /// Look up `key`, marking it most-recently-used on a hit.
+    pub fn get(&self, key: &K) -> ObjectCacheResult<V> {
+        let mut state = self.state.lock().unwrap();
+        if !state.entries.contains_key(key) {
+            self.stats.misses.fetch_add(1, Ordering::Relaxed);
+            return ObjectCacheResult::Miss;
+        }
+        self.stats.hits.fetch_add(1, Ordering::Relaxed);
+        state.order.retain(|k| k != key);
+        state.order.push_back(key.clone());
+        ObjectCacheResult::Cached(Arc::clone(&state.entries[key].value))
+    }
+
+    /// Insert `value` under `key`, charging `size` bytes against the budget, evicting
+    /// least-recently-used entries (oldest first) until the total fits again.
+    pub fn insert(&self, key: K, value: Arc<V>, size: usize) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(old) = state.entries.remove(&key) {
+            state.total_bytes -= old.size;
+            state.order.retain(|k| k != &key);
+        }
+        state.order.push_back(key.clone());
+        state.total_bytes += size;
+        state.entries.insert(key, CacheEntry { value, size });
+
+        while state.total_bytes > self.budget_bytes {
+            let Some(lru_key) = state.order.pop_front() else {
+                break;
+            };
+            if let Some(evicted) = state.entries.remove(&lru_key) {
+                state.total_bytes -= evicted.size;
+            }
+        }
+    }
+
+    /// Get `key` from the cache, or open it via `open` and insert it (sized via `size_of`) on a
+    /// miss -- the common case callers actually want.
+    pub fn get_or_open<E>(
+        &self,
+        key: K,
+        size_of: impl FnOnce(&V) -> usize,
+        open: impl FnOnce() -> Result<V, E>,
+    ) -> Result<Arc<V>, E> {
+        if let ObjectCacheResult::Cached(value) = self.get(&key) {
+            return Ok(value);
+        }
+        let value = Arc::new(open()?);
+        let size = size_of(&value);
+        self.insert(key, Arc::clone(&value), size);
+        Ok(value)
+    }
+}
+
+impl<T: Types, C: EntryCodec<T>, H: ReadObjectHandle> SimplePersistentLayerReader<T, C, H> {
+    /// Rough size of this reader's decoded, in-memory state: just the sparse index, since
+    /// blocks themselves are read (and not cached) on demand by `read_block_containing`. Used
+    /// as the `size` a [`LayerCache`] charges an entry against its byte budget.
+    pub fn approximate_decoded_size(&self) -> usize {
+        self.index.len() * std::mem::size_of::<BlockIndexEntry<T::Key>>()
+    }
+
+    /// Like [`Self::open`], but consults `cache` first (keyed by `id`, typically something like
+    /// a layer's file path or object key) and shares the decoded reader -- including its
+    /// in-memory block index -- with any other concurrent caller opening the same layer, rather
+    /// than re-reading and re-parsing the footer and index from scratch every time.
+    ///
+    /// A concrete `HistoricStuff`/`HistoricLayer` implementation backed by persistent layers
+    /// (none exists in this crate yet -- see the honest gaps noted on `Layer`/`PersistentLayer`
+    /// in `pageserver`'s `storage_layer` module) would hold one `LayerCache` per tenant/timeline
+    /// and call this from its `get_reconstruct_path` instead of unconditionally opening.
+    pub fn open_cached<K: std::hash::Hash + Eq + Clone>(
+        cache: &LayerCache<K, Self>,
+        id: K,
+        handle: H,
+        total_len: u64,
+    ) -> Result<Arc<Self>, OpenPersistentLayerError> {
+        cache.get_or_open(
+            id,
+            Self::approximate_decoded_size,
+            || Self::open(handle, total_len),
+        )
+    }
+}
+
+/// Number of forward-pointer levels a [`SkipListNode`] can have. fxfs's skip_list_layer picks
+/// a similarly small constant. With each level kept with probability 1/2, searches stay
+/// logarithmic up to about 2^12 entries; beyond that the top level's chain grows linearly.
+const SKIP_LIST_MAX_HEIGHT: usize = 12;
+
+/// `(key, lsn)` ordering used throughout the skip list: ascending by key, then ascending by
+/// Lsn within a key -- the same order `get_reconstruct_path`'s `BTreeMap<Key, BTreeMap<Lsn,
+/// _>>` test impl iterates before reversing for newest-first output.
+fn skip_list_cmp<T: Types>(a: (T::Key, T::Lsn), b: (T::Key, T::Lsn)) -> std::cmp::Ordering {
+    a.0.cmp(&b.0).then(a.1.cmp(&b.1))
+}
+
+struct SkipListNode<T: Types> {
+    key: T::Key,
+    lsn: T::Lsn,
+    delta: T::DeltaRecord,
+    next: [AtomicPtr<SkipListNode<T>>; SKIP_LIST_MAX_HEIGHT],
+}
+
+impl<T: Types> SkipListNode<T> {
+    /// The node doesn't record its height: `insert` picks it and links up that many of the
+    /// `next` slots; the remaining slots stay null.
+    fn alloc(key: T::Key, lsn: T::Lsn, delta: T::DeltaRecord) -> *mut Self {
+        Box::into_raw(Box::new(SkipListNode {
+            key,
+            lsn,
+            delta,
+            next: std::array::from_fn(|_| AtomicPtr::new(std::ptr::null_mut())),
+        }))
+    }
+}
+
+/// A concurrent, insert-only skip list standing in for `InMemoryLayer`'s `Mutex<BTreeMap<..>>`
+/// representation. Modeled on fxfs's `skip_list_layer`: a single writer (this type's `put`/
+/// `freeze` both take `&mut self`, same as the existing impl, so there is never more than one
+/// at a time by construction) publishes new nodes with `Release` stores, and any number of
+/// concurrent readers (`get` takes `&self`) traverse with `Acquire` loads, so a reader racing
+/// an in-progress `put` always sees either the old chain or the fully-linked new node, never a
+/// torn one. There's no delete path -- the layer is only ever appended to until `freeze()`,
+/// matching how it's actually used -- so there's no concurrent-reclamation hazard to solve
+/// here; nodes are freed in bulk when the whole list is dropped.
+pub struct SkipListInMemoryLayer<T: Types> {
+    head: [AtomicPtr<SkipListNode<T>>; SKIP_LIST_MAX_HEIGHT],
+    frozen: AtomicBool,
+    /// Plain (non-atomic) writer-only state: `put`/`freeze` take `&mut self`, so nothing else
+    /// can be touching this concurrently.
+    rng_state: u64,
+    len: usize,
+    _types: PhantomData<T>,
+}
+
+impl<T: Types> Default for SkipListInMemoryLayer<T> {
+    fn default() -> Self {
+        SkipListInMemoryLayer {
+            head: std::array::from_fn(|_| AtomicPtr::new(std::ptr::null_mut())),
+            frozen: AtomicBool::new(false),
+            rng_state: 0x2545_f491_4f6c_dd1d,
+            len: 0,
+            _types: PhantomData,
+        }
+    }
+}
+
+impl<T: Types> std::fmt::Debug for SkipListInMemoryLayer<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SkipListInMemoryLayer")
+            .field("len", &self.len)
+            .field("frozen", &self.frozen.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+impl<T: Types> Drop for SkipListInMemoryLayer<T> {
+    fn drop(&mut self) {
+        let mut node = *self.head[0].get_mut();
+        while !node.is_null() {
+            // SAFETY: `&mut self` here means no reader can be concurrently traversing this
+            // list, so every node reachable from `head[0]` is uniquely ours to reclaim, and
+            // each is reachable exactly once via the level-0 chain.
+            let mut boxed = unsafe { Box::from_raw(node) };
+            node = *boxed.next[0].get_mut();
+        }
+    }
+}
+
+impl<T: Types> Clone for SkipListInMemoryLayer<T>
+where
+    T::DeltaRecord: Clone,
+{
+    fn clone(&self) -> Self {
+        let mut copy = SkipListInMemoryLayer::default();
+        let mut node = self.next_at(std::ptr::null_mut(), 0);
+        while !node.is_null() {
+            // SAFETY: `node` came from a just-performed acquire load off a chain this shared
+            // reference is allowed to read; the node itself is never freed while `self` lives.
+            let n = unsafe { &*node };
+            copy.insert(n.key, n.lsn, n.delta.clone())
+                .expect("re-inserting entries from an already (key, lsn)-ordered, non-overlapping source can't collide");
+            node = self.next_at(node, 0);
+        }
+        if self.frozen.load(Ordering::Acquire) {
+            copy.freeze();
+        }
+        copy
+    }
+}
+
+impl<T: Types> SkipListInMemoryLayer<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn next_at(&self, node: *mut SkipListNode<T>, level: usize) -> *mut SkipListNode<T> {
+        if node.is_null() {
+            self.head[level].load(Ordering::Acquire)
+        } else {
+            // SAFETY: `node` is always either null or a pointer previously published by this
+            // same list's `insert`, which never frees a node while the list is alive.
+            unsafe { (*node).next[level].load(Ordering::Acquire) }
+        }
+    }
+
+    fn store_next(&self, node: *mut SkipListNode<T>, level: usize, new: *mut SkipListNode<T>) {
+        if node.is_null() {
+            self.head[level].store(new, Ordering::Release);
+        } else {
+            // SAFETY: see `next_at`.
+            unsafe { (*node).next[level].store(new, Ordering::Release) };
+        }
+    }
+
+    fn random_height(&mut self) -> usize {
+        // xorshift64: cheap and plenty uniform for picking a tower height, and this is
+        // writer-only state so there's no need for it to be an atomic.
+        let mut x = self.rng_state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.rng_state = x;
+        let mut height = 1;
+        while height < SKIP_LIST_MAX_HEIGHT && (x >> (height - 1)) & 1 == 1 {
+            height += 1;
+        }
+        height
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(
+        &mut self,
+        key: T::Key,
+        lsn: T::Lsn,
+        delta: T::DeltaRecord,
+    ) -> Result<(), (T::DeltaRecord, InMemoryLayerPutError)> {
+        if self.frozen.load(Ordering::Acquire) {
+            return Err((delta, InMemoryLayerPutError::Frozen));
+        }
+
+        // Descend from the top level, recording at each level the last node strictly before
+        // the insertion point -- the classic skip list search, just also checking for an
+        // exact-match collision along the way.
+        let mut update: [*mut SkipListNode<T>; SKIP_LIST_MAX_HEIGHT] =
+            [std::ptr::null_mut(); SKIP_LIST_MAX_HEIGHT];
+        let mut current: *mut SkipListNode<T> = std::ptr::null_mut();
+        for level in (0..SKIP_LIST_MAX_HEIGHT).rev() {
+            loop {
+                let next = self.next_at(current, level);
+                if next.is_null() {
+                    break;
+                }
+                // SAFETY: see `next_at`.
+                let next_ref = unsafe { &*next };
+                match skip_list_cmp::<T>((next_ref.key, next_ref.lsn), (key, lsn)) {
+                    std::cmp::Ordering::Less => current = next,
+                    std::cmp::Ordering::Equal => {
+                        return Err((delta, InMemoryLayerPutError::AlreadyHaveRecordForKeyAndLsn));
+                    }
+                    std::cmp::Ordering::Greater => break,
+                }
+            }
+            update[level] = current;
+        }
+
+        let height = self.random_height();
+        let node = SkipListNode::alloc(key, lsn, delta);
+        for level in 0..height {
+            let next = self.next_at(update[level], level);
+            // SAFETY: `node` was just allocated by us and isn't reachable by any reader yet,
+            // so a plain store (no ordering requirement) is enough here.
+            unsafe { (*node).next[level].store(next, Ordering::Relaxed) };
+        }
+        // Publish last: once these stores land, a concurrent reader's acquire load is
+        // guaranteed to observe a fully-initialized node (its own next[] pointers were
+        // written above, with a Relaxed store that's ordered before this Release store by
+        // program order on this thread, and by the Release/Acquire pairing on the reader's
+        // side).
+        for level in 0..height {
+            self.store_next(update[level], level, node);
+        }
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn get(&self, key: T::Key, lsn: T::Lsn) -> Vec<(T::Lsn, T::DeltaRecord)>
+    where
+        T::DeltaRecord: Clone,
+    {
+        // Find the predecessor of the first node with this key, the same descent as insert's
+        // but comparing on key alone (every entry for a key is contiguous in (key, lsn) order).
+        let mut current: *mut SkipListNode<T> = std::ptr::null_mut();
+        for level in (0..SKIP_LIST_MAX_HEIGHT).rev() {
+            loop {
+                let next = self.next_at(current, level);
+                if next.is_null() {
+                    break;
+                }
+                // SAFETY: see `next_at`.
+                let next_key = unsafe { (*next).key };
+                if next_key < key {
+                    current = next;
+                } else {
+                    break;
+                }
+            }
+        }
+
+        let mut out = Vec::new();
+        let mut node = self.next_at(current, 0);
+        while !node.is_null() {
+            // SAFETY: see `next_at`; concurrent inserts only ever append, they never unlink or
+            // mutate an already-published node, so this reference stays valid for as long as
+            // we hold it.
+            let n = unsafe { &*node };
+            if n.key != key {
+                break;
+            }
+            if n.lsn <= lsn {
+                out.push((n.lsn, n.delta.clone()));
+            }
+            node = self.next_at(node, 0);
+        }
+        out.reverse(); // newest first, matching the existing Mutex<BTreeMap<..>> impl's contract
+        out
+    }
+
+    pub fn freeze(&mut self) {
+        self.frozen.store(true, Ordering::Release);
+    }
+}
+
+impl<T: Types> InMemoryLayer for SkipListInMemoryLayer<T>
+where
+    T::DeltaRecord: Clone,
+{
+    type Types = T;
+
+    fn put(
+        &mut self,
+        key: T::Key,
+        lsn: T::Lsn,
+        delta: T::DeltaRecord,
+    ) -> Result<(), (T::DeltaRecord, InMemoryLayerPutError)> {
+        self.insert(key, lsn, delta)
+    }
+
+    fn get(&self, key: T::Key, lsn: T::Lsn) -> Vec<(T::Lsn, T::DeltaRecord)> {
+        SkipListInMemoryLayer::get(self, key, lsn)
+    }
+
+    fn freeze(&mut self) {
+        SkipListInMemoryLayer::freeze(self)
+    }
+
+    fn approximate_len(&self) -> usize {
+        SkipListInMemoryLayer::len(self)
+    }
+}
+
+// SAFETY: every `SkipListNode` reachable from `head` is heap-allocated and only ever linked in
+// by `insert` (which takes `&mut self`, so at most one thread runs it at a time) using
+// `Release` stores that a reader's `Acquire` loads pair with; nothing is ever mutated in place
+// or unlinked, so sharing `&SkipListInMemoryLayer<T>` across threads for concurrent `get`
+// calls is sound as long as `T::Key`/`T::Lsn`/`T::DeltaRecord` are themselves `Send`/`Sync`.
+unsafe impl<T: Types> Sync for SkipListInMemoryLayer<T>
+where
+    T::Key: Sync,
+    T::Lsn: Sync,
+    T::DeltaRecord: Sync,
+{
+}
+unsafe impl<T: Types> Send for SkipListInMemoryLayer<T>
+where
+    T::Key: Send,
+    T::Lsn: Send,
+    T::DeltaRecord: Send,
+{
+}
+
+/// Lifecycle of whatever's currently sitting in a [`State`]'s `inmem` slot, recorded during the
+/// window between `freeze()` and the `advance()` call that swaps the whole `State` out for a
+/// fresh one with an empty, `Open` layer. The writer paths set it; nothing in this crate reads
+/// it back, and it has no effect on reads (`inmem` stays populated, and therefore readable,
+/// right up until `advance` replaces it).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LayerLifecycle {
+    /// Still accepting writes.
+    Open,
+    /// `freeze()` has been called; no longer accepting writes.
+    Frozen,
+    /// Being written out (`make_historic`) by [`ReadWriter::flush_current_layer_if_any`] or the
+    /// background task spawned by [`ReadWriter::spawn_flusher`].
+    Flushing,
+    /// Durable and folded into `historic`, moments away from this whole `State` being replaced
+    /// by `advance`.
+    Flushed,
+}
+
 struct State<T: Types> {
     _types: PhantomData<T>,
     inmem: Mutex<Option<T::InMemoryLayer>>,
+    lifecycle: Mutex<LayerLifecycle>,
+    /// Highest `Lsn` successfully written into `inmem` so far, if any -- tracked so
+    /// [`ReadWriter::flush_current_layer_if_any`] has something correct to `advance` to even
+    /// when it's invoked by the background flusher rather than by a `put` that already knows
+    /// the `Lsn` it just wrote.
+    max_lsn: Mutex<Option<T::Lsn>>,
     historic: T::HistoricStuff,
 }
 
+/// Record that `lsn` was just written into `state`'s current layer, keeping whichever of the
+/// previous value and `lsn` is greater (callers always write in non-decreasing `Lsn` order, so
+/// in practice this just becomes `lsn`, but `max` is a cheap safeguard against relying on that).
+fn record_max_lsn<T: Types>(state: &State<T>, lsn: T::Lsn) {
+    let mut guard = state.max_lsn.lock().unwrap();
+    *guard = Some(match *guard {
+        Some(prev) if prev > lsn => prev,
+        _ => lsn,
+    });
+}
+
 pub struct Reader<T: Types> {
     shared: Wait<T::LsnCounter, T::Lsn, Arc<State<T>>>,
 }
@@ -77,6 +1065,8 @@ pub fn empty<T: Types>(
     let state = Arc::new(State {
         _types: PhantomData::<T>::default(),
         inmem: Mutex::new(None),
+        lifecycle: Mutex::new(LayerLifecycle::Open),
+        max_lsn: Mutex::new(None),
         historic: historic,
     });
     let (wait_only, advance) = SeqWait::new(lsn, state).split_spmc();
@@ -93,16 +1083,37 @@ pub enum GetError {
     GetReconstructPath(#[from] GetReconstructPathError),
 }
 
+/// The per-layer cursors relevant to one `(key, lsn)` lookup, merged into global order. Holds
+/// the iterator rather than two eagerly-materialized `Vec`s, so a caller can stop pulling
+/// records (e.g. as soon as a full-page image is reached) without draining the rest of the
+/// merge. Every layer on the reconstruct path still gets a cursor, peeked once, when the
+/// merge is built.
 pub struct ReconstructWork<T: Types> {
     key: T::Key,
     lsn: T::Lsn,
-    inmem_records: Vec<T::DeltaRecord>,
-    historic_path: Vec<T::HistoricLayer>,
+    merged: MergeIterator<T>,
+}
+
+impl<T: Types> ReconstructWork<T> {
+    pub fn key(&self) -> T::Key {
+        self.key
+    }
+
+    pub fn lsn(&self) -> T::Lsn {
+        self.lsn
+    }
+
+    /// Pull the next record off the merge, newest-first.
+    pub fn next(&mut self) -> Option<(T::Key, T::Lsn, T::DeltaRecord)> {
+        self.merged.next()
+    }
 }
 
 impl<T: Types> Reader<T> {
     pub async fn get(&self, key: T::Key, lsn: T::Lsn) -> Result<ReconstructWork<T>, GetError> {
         let state = self.shared.wait_for(lsn).await?;
+
+        let mut cursors: Vec<Box<dyn MergeCursor<T> + Send>> = Vec::new();
         let inmem_records = state
             .inmem
             .lock()
@@ -110,16 +1121,176 @@ impl<T: Types> Reader<T> {
             .as_ref()
             .map(|iml| iml.get(key, lsn))
             .unwrap_or_default();
+        cursors.push(Box::new(VecMergeCursor::new(
+            inmem_records
+                .into_iter()
+                .map(|(record_lsn, delta)| (key, record_lsn, delta))
+                .collect(),
+        )));
+
         let historic_path = state.historic.get_reconstruct_path(key, lsn)?;
+        for layer in &historic_path {
+            cursors.push(layer.cursor(key, lsn));
+        }
+
         Ok(ReconstructWork {
             key,
             lsn,
-            inmem_records,
-            historic_path,
+            merged: MergeIterator::new(cursors),
         })
     }
 }
 
+/// One buffered write inside a [`Transaction`].
+struct Mutation<T: Types> {
+    key: T::Key,
+    lsn: T::Lsn,
+    delta: T::DeltaRecord,
+}
+
+/// Flags mirroring the external design this is modeled on (fxfs's `object_store::transaction`
+/// `Options`), so internal callers have a stable place to express "don't treat this one like
+/// a normal foreground write". Neither flag changes `commit`'s behavior: no
+/// `InMemoryLayer` impl in this crate currently does its own proactive space check, so there's
+/// nothing for `skip_space_check` to skip, and `LayerFull` rollover isn't recursive, so there's
+/// nothing for `is_flush` to need to bypass.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TransactionOptions {
+    pub skip_space_check: bool,
+    pub is_flush: bool,
+}
+
+/// A batch of writes that become visible together: [`Transaction::commit`] applies every
+/// buffered [`Mutation`] into the in-memory layer under one exclusive section and performs a
+/// single `advance(max_lsn, ..)`, so a concurrent `Reader::get(_, lsn)` can only ever observe
+/// all of a transaction's mutations or none of them, never a partially-applied subset.
+pub struct Transaction<'a, T: Types> {
+    read_writer: &'a mut ReadWriter<T>,
+    options: TransactionOptions,
+    mutations: Vec<Mutation<T>>,
+}
+
+impl<'a, T: Types> Transaction<'a, T> {
+    /// Buffer one more write. Nothing is applied until [`Self::commit`].
+    pub fn put(&mut self, key: T::Key, lsn: T::Lsn, delta: T::DeltaRecord) {
+        self.mutations.push(Mutation { key, lsn, delta });
+    }
+
+    pub async fn commit(self) -> tokio::io::Result<()> {
+        self.read_writer
+            .commit_transaction(self.mutations, self.options)
+            .await
+    }
+}
+
+impl<T: Types> ReadWriter<T> {
+    /// Start a transaction with default [`TransactionOptions`]. See [`Transaction`].
+    pub fn transaction(&mut self) -> Transaction<'_, T> {
+        self.transaction_with_options(TransactionOptions::default())
+    }
+
+    pub fn transaction_with_options(&mut self, options: TransactionOptions) -> Transaction<'_, T> {
+        Transaction {
+            read_writer: self,
+            options,
+            mutations: Vec::new(),
+        }
+    }
+
+    /// Apply every mutation in `mutations` and make them all visible with a single `advance`.
+    ///
+    /// Mutations are sorted into `(Key, Lsn)` order and de-duplicated first (keeping the
+    /// last-buffered mutation for any repeated `(key, lsn)`, i.e. later writes in the same
+    /// transaction win). If a mutation hits `LayerFull` partway through, the current layer is
+    /// frozen and flushed to historic, a fresh in-memory layer is installed, and the rest of
+    /// the batch -- including the mutation that didn't fit -- is replayed into it before the
+    /// one `advance` call, so the Lsn a reader waits on only becomes visible once every
+    /// mutation for this transaction is durable in-memory.
+    async fn commit_transaction(
+        &mut self,
+        mut mutations: Vec<Mutation<T>>,
+        _options: TransactionOptions,
+    ) -> tokio::io::Result<()> {
+        let Some(max_lsn) = mutations.iter().map(|m| m.lsn).max() else {
+            return Ok(());
+        };
+
+        // Stable-sort in reverse-then-forward so that, among mutations sharing a (key, lsn),
+        // the last-buffered one ends up first within its run; dedup_by then keeps that one and
+        // drops the earlier, now-superseded duplicates.
+        mutations.reverse();
+        mutations.sort_by(|a, b| skip_list_cmp::<T>((a.key, a.lsn), (b.key, b.lsn)));
+        mutations.dedup_by(|a, b| a.key == b.key && a.lsn == b.lsn);
+
+        let mut current_state: Arc<State<T>> = self.shared.get_current_data();
+        // Apply the batch to a private copy of the current layer, so the published layer stays
+        // readable and untouched until the batch has fully applied.
+        let mut inmem = current_state
+            .inmem
+            .try_lock()
+            // XXX: use the Advance as witness and only allow witness to access inmem in write mode
+            .expect("we are the only ones with the Advance at hand")
+            .clone()
+            .unwrap_or_else(T::InMemoryLayer::default);
+        let mut rolled_state: Option<Arc<State<T>>> = None;
+
+        for Mutation { key, lsn, mut delta } in mutations {
+            loop {
+                match inmem.put(key, lsn, delta) {
+                    Ok(()) => {
+                        record_max_lsn(&current_state, lsn);
+                        break;
+                    }
+                    Err((_, InMemoryLayerPutError::Frozen)) => {
+                        unreachable!("this transaction is the sole writer into `inmem` for its entire commit, so nothing else could have frozen it out from under us")
+                    }
+                    Err((_, InMemoryLayerPutError::AlreadyHaveRecordForKeyAndLsn)) => {
+                        // The in-batch dedup above only removes duplicate `(key, lsn)` pairs
+                        // within this one transaction; it can't know about records a *previous*
+                        // transaction already committed. That's a real possibility here (two
+                        // callers racing to commit the same (key, lsn)), so report it rather
+                        // than panicking the process. `inmem` is a private copy, so returning
+                        // here leaves the published layer exactly as it was: none of this
+                        // transaction's mutations are applied to it or published.
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::AlreadyExists,
+                            "transaction already has a record for this (key, lsn)",
+                        ));
+                    }
+                    Err((returned_delta, InMemoryLayerPutError::LayerFull)) => {
+                        inmem.freeze();
+                        *current_state.lifecycle.lock().unwrap() = LayerLifecycle::Frozen;
+                        let new_historic = current_state.historic.make_historic(inmem.clone());
+                        let new_state = Arc::new(State {
+                            _types: PhantomData::<T>::default(),
+                            inmem: Mutex::new(None),
+                            lifecycle: Mutex::new(LayerLifecycle::Open),
+                            max_lsn: Mutex::new(None),
+                            historic: new_historic,
+                        });
+                        current_state = new_state.clone();
+                        rolled_state = Some(new_state);
+                        inmem = T::InMemoryLayer::default();
+                        delta = returned_delta;
+                        // loop again: retry this same mutation against the fresh layer
+                    }
+                }
+            }
+        }
+
+        {
+            let mut guard = current_state
+                .inmem
+                .try_lock()
+                .expect("still the only writer");
+            *guard = Some(inmem);
+        }
+
+        self.shared.advance(max_lsn, rolled_state);
+        Ok(())
+    }
+}
+
 impl<T: Types> ReadWriter<T> {
     pub async fn put(
         &mut self,
@@ -127,42 +1298,114 @@ impl<T: Types> ReadWriter<T> {
         lsn: T::Lsn,
         delta: T::DeltaRecord,
     ) -> tokio::io::Result<()> {
+        // No flusher is driving this `ReadWriter`, so there's no threshold worth checking --
+        // `usize::MAX` never crosses, and the only way the layer ever gets rolled over is the
+        // `LayerFull` fallback `put_checking_threshold` always handles regardless.
+        self.put_checking_threshold(key, lsn, delta, usize::MAX)
+            .await?;
+        Ok(())
+    }
+
+    /// Does the actual work of `put`, additionally reporting whether the (possibly just-rolled)
+    /// layer's `approximate_len()` has reached `size_threshold`. [`FlusherWriteHandle::put`]
+    /// uses that to nudge the background flusher as soon as it's worth flushing, rather than
+    /// waiting for its next timer tick.
+    async fn put_checking_threshold(
+        &mut self,
+        key: T::Key,
+        lsn: T::Lsn,
+        delta: T::DeltaRecord,
+        size_threshold: usize,
+    ) -> tokio::io::Result<bool> {
         let shared: Arc<State<T>> = self.shared.get_current_data();
         let mut inmem_guard = shared
             .inmem
             .try_lock()
             // XXX: use the Advance as witness and only allow witness to access inmem in write mode
             .expect("we are the only ones with the Advance at hand");
-        let inmem = inmem_guard.get_or_insert_with(|| T::InMemoryLayer::default());
+        let inmem = inmem_guard.get_or_insert_with(T::InMemoryLayer::default);
         match inmem.put(key, lsn, delta) {
             Ok(()) => {
+                let crossed = inmem.approximate_len() >= size_threshold;
+                record_max_lsn(&shared, lsn);
                 self.shared.advance(lsn, None);
+                Ok(crossed)
             }
-            Err((delta, InMemoryLayerPutError::Frozen)) => {
+            Err((_delta, InMemoryLayerPutError::Frozen)) => {
                 unreachable!("this method is &mut self, so, Rust guarantees that we are the only ones who can put() into the inmem layer, and if we freeze it as part of put, we make sure we don't try to put() again")
             }
-            Err((delta, InMemoryLayerPutError::AlreadyHaveRecordForKeyAndLsn)) => {
-                todo!("propagate error to caller")
+            Err((_delta, InMemoryLayerPutError::AlreadyHaveRecordForKeyAndLsn)) => {
+                // Mirrors `commit_transaction`'s handling of the same error: report it to
+                // the caller instead of panicking. `inmem_guard` is dropped normally on
+                // return, so the layer is left exactly as it was before this `put`.
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    "already have a record for this (key, lsn)",
+                ))
             }
-            Err((delta, InMemoryLayerPutError::LayerFull)) => {
+            Err((mut delta, InMemoryLayerPutError::LayerFull)) => {
                 inmem.freeze();
-                let inmem_clone = inmem.clone();
-                drop(inmem);
+                *shared.lifecycle.lock().unwrap() = LayerLifecycle::Frozen;
+                let historic_source = inmem.clone();
                 drop(inmem_guard);
-                todo!("write out to disk; does the layer map need to distinguish between writing out and finished writing out?");
-                let new_historic = shared.historic.make_historic(inmem_clone);
+
+                *shared.lifecycle.lock().unwrap() = LayerLifecycle::Flushing;
+                let new_historic = shared.historic.make_historic(historic_source);
                 let new_state = Arc::new(State {
                     _types: PhantomData::<T>::default(),
                     inmem: Mutex::new(None),
+                    lifecycle: Mutex::new(LayerLifecycle::Open),
+                    max_lsn: Mutex::new(Some(lsn)),
                     historic: new_historic,
                 });
+                *shared.lifecycle.lock().unwrap() = LayerLifecycle::Flushed;
+
+                // The record that didn't fit still needs a home: replay it into the fresh
+                // layer before publishing, the same roll-forward `commit_transaction` does for
+                // a whole batch, just for this one mutation.
+                let mut new_inmem = T::InMemoryLayer::default();
+                loop {
+                    match new_inmem.put(key, lsn, delta) {
+                        Ok(()) => break,
+                        Err((_, InMemoryLayerPutError::Frozen)) => {
+                            unreachable!("a layer we just created can't already be frozen")
+                        }
+                        Err((_, InMemoryLayerPutError::AlreadyHaveRecordForKeyAndLsn)) => {
+                            // `new_inmem` is fresh and never published (no `advance` call
+                            // has happened yet), so there's nothing to unwind -- just report
+                            // the collision to the caller the same way the non-rollover path
+                            // above does.
+                            return Err(std::io::Error::new(
+                                std::io::ErrorKind::AlreadyExists,
+                                "already have a record for this (key, lsn)",
+                            ));
+                        }
+                        Err((returned_delta, InMemoryLayerPutError::LayerFull)) => {
+                            // Could only happen for an `InMemoryLayer` impl with a capacity too
+                            // small to hold even one record; none in this crate behaves that
+                            // way today.
+                            delta = returned_delta;
+                            continue;
+                        }
+                    }
+                }
+                let crossed = new_inmem.approximate_len() >= size_threshold;
+                *new_state.inmem.lock().unwrap() = Some(new_inmem);
+
                 self.shared.advance(lsn, Some(new_state));
+                Ok(crossed)
             }
         }
-        Ok(())
     }
 
-    pub async fn force_flush(&mut self) -> tokio::io::Result<()> {
+    /// Freeze whatever's in `inmem`, if anything, write it out (`make_historic`), and publish
+    /// the result with a single `advance` -- the actual flush work, shared by the plain
+    /// `force_flush` below and the background task [`ReadWriter::spawn_flusher`] starts.
+    ///
+    /// This used to build `new_state` and then never call `advance`, silently discarding the
+    /// just-flushed layer and leaving the old one stuck `Frozen` (so the next `put` would keep
+    /// hitting `InMemoryLayerPutError::Frozen` forever). Fixed here by always publishing.
+    async fn flush_current_layer_if_any(&mut self) -> tokio::io::Result<()> {
         let shared = self.shared.get_current_data();
         let mut inmem_guard = shared
             .inmem
@@ -174,16 +1417,40 @@ impl<T: Types> ReadWriter<T> {
             return Ok(());
         };
         inmem.freeze();
+        *shared.lifecycle.lock().unwrap() = LayerLifecycle::Frozen;
         let inmem_clone = inmem.clone();
+        drop(inmem_guard);
+
+        *shared.lifecycle.lock().unwrap() = LayerLifecycle::Flushing;
         let new_historic = shared.historic.make_historic(inmem_clone);
         let new_state = Arc::new(State {
             _types: PhantomData::<T>::default(),
             inmem: Mutex::new(None),
+            lifecycle: Mutex::new(LayerLifecycle::Open),
+            max_lsn: Mutex::new(None),
             historic: new_historic,
         });
+        *shared.lifecycle.lock().unwrap() = LayerLifecycle::Flushed;
+
+        // Advance to the highest `Lsn` actually written into the layer we just flushed --
+        // tracked in `max_lsn` precisely so this is available even when, as here, nothing
+        // handed this method an `Lsn` of its own to advance to (unlike `put`, which always has
+        // the one it just wrote).
+        let flushed_lsn = shared
+            .max_lsn
+            .lock()
+            .unwrap()
+            .expect("a layer that had something to freeze must have recorded at least one Lsn");
+        self.shared.advance(flushed_lsn, Some(new_state));
         Ok(())
     }
 
+    /// Freeze and flush the current in-memory layer, if any, and don't return until it's
+    /// actually durable (folded into `historic` and published via `advance`).
+    pub async fn force_flush(&mut self) -> tokio::io::Result<()> {
+        self.flush_current_layer_if_any().await
+    }
+
     pub async fn get_nowait(
         &self,
         key: T::Key,
@@ -193,6 +1460,168 @@ impl<T: Types> ReadWriter<T> {
     }
 }
 
+/// State shared between a [`FlusherWriteHandle`] and the background task
+/// [`ReadWriter::spawn_flusher`] spawns for it: a way to nudge the task early (instead of waiting for its next timer tick)
+/// and a way to learn when it's finished a flush.
+struct FlusherShared {
+    /// Signaled by `put` once the current layer's `approximate_len()` crosses the configured
+    /// threshold, and by `force_flush` to request an immediate flush.
+    notify: tokio::sync::Notify,
+    /// Signaled by [`FlusherHandle::shutdown`] and by its `Drop` impl (which also runs at the
+    /// end of `shutdown`), to ask the background task to perform one last flush and then exit.
+    shutdown: tokio::sync::Notify,
+    /// Signaled by the background task every time it finishes a flush (including the final one
+    /// on shutdown), so `force_flush` can wait for its own nudge to have actually been acted on.
+    flushed: tokio::sync::Notify,
+}
+
+/// A cheaply-clonable handle for driving a [`ReadWriter`] that's shared with a background
+/// flusher task (see [`ReadWriter::spawn_flusher`]). `put` no longer freezes and flushes the
+/// layer inline when it gets full enough to be worth writing out -- it just nudges the flusher
+/// and keeps going, same as `put` itself still does as a last resort if the flusher falls
+/// behind and the layer hits a hard `LayerFull`.
+pub struct FlusherWriteHandle<T: Types> {
+    read_writer: Arc<tokio::sync::Mutex<ReadWriter<T>>>,
+    flusher: Arc<FlusherShared>,
+    layer_size_threshold: usize,
+}
+
+impl<T: Types> Clone for FlusherWriteHandle<T> {
+    fn clone(&self) -> Self {
+        FlusherWriteHandle {
+            read_writer: Arc::clone(&self.read_writer),
+            flusher: Arc::clone(&self.flusher),
+            layer_size_threshold: self.layer_size_threshold,
+        }
+    }
+}
+
+impl<T: Types> FlusherWriteHandle<T> {
+    pub async fn put(&self, key: T::Key, lsn: T::Lsn, delta: T::DeltaRecord) -> tokio::io::Result<()> {
+        let crossed = {
+            let mut read_writer = self.read_writer.lock().await;
+            read_writer
+                .put_checking_threshold(key, lsn, delta, self.layer_size_threshold)
+                .await?
+        };
+        if crossed {
+            self.flusher.notify.notify_one();
+        }
+        Ok(())
+    }
+
+    /// Ask the flusher to flush the current layer right now, and wait for it to do so.
+    ///
+    /// This waits for the *next* flush the background task completes, not specifically one
+    /// covering everything written before this call -- with a single flusher task and a single
+    /// writer this is the same thing, but a caller juggling several concurrent `force_flush`es
+    /// could in principle have one of them observe an unrelated, already-in-flight flush finish
+    /// instead of the one it asked for. Good enough for the single-writer case this crate
+    /// actually has today.
+    pub async fn force_flush(&self) -> tokio::io::Result<()> {
+        let flushed = self.flusher.flushed.notified();
+        self.flusher.notify.notify_one();
+        flushed.await;
+        Ok(())
+    }
+}
+
+/// Owns the background task spawned by [`ReadWriter::spawn_flusher`]. Dropping this (without
+/// calling [`Self::shutdown`] first) still asks the task to perform its final flush before
+/// exiting, but can't wait for it -- `Drop` can't `.await`. Callers that need the final flush to
+/// have actually landed (e.g. right before process exit) should call `shutdown().await`
+/// instead of just letting this drop.
+pub struct FlusherHandle {
+    shared: Arc<FlusherShared>,
+    join: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl FlusherHandle {
+    /// Ask the flusher to perform one last flush of whatever layer is currently open, then wait
+    /// for it to finish and for the task to exit.
+    pub async fn shutdown(mut self) {
+        self.shared.shutdown.notify_one();
+        if let Some(join) = self.join.take() {
+            let _ = join.await;
+        }
+    }
+}
+
+impl Drop for FlusherHandle {
+    fn drop(&mut self) {
+        // Best-effort wake-up; see the type's doc comment for why this can't wait for the
+        // resulting flush to actually finish.
+        self.shared.shutdown.notify_one();
+    }
+}
+
+impl<T: Types + Send + 'static> ReadWriter<T> {
+    /// Hand `self` off for shared use by the caller (via the returned [`FlusherWriteHandle`])
+    /// and a background flusher task (tracked by the returned [`FlusherHandle`]).
+    ///
+    /// Every `interval`, or as soon as a [`FlusherWriteHandle::put`] notices the current layer's
+    /// `approximate_len()` reach `layer_size_threshold`, the task calls
+    /// [`ReadWriter::flush_current_layer_if_any`]: freeze the current layer, write it out
+    /// (`make_historic`), and publish the result with a single `advance`. This moves that work
+    /// off the foreground `put` path for the common case; `put` still falls back to doing it
+    /// inline if the flusher hasn't kept up and the layer actually hits `LayerFull`.
+    pub fn spawn_flusher(
+        self,
+        interval: Duration,
+        layer_size_threshold: usize,
+    ) -> (FlusherWriteHandle<T>, FlusherHandle) {
+        let read_writer = Arc::new(tokio::sync::Mutex::new(self));
+        let flusher = Arc::new(FlusherShared {
+            notify: tokio::sync::Notify::new(),
+            shutdown: tokio::sync::Notify::new(),
+            flushed: tokio::sync::Notify::new(),
+        });
+
+        let join = tokio::spawn(run_flusher(
+            Arc::clone(&read_writer),
+            Arc::clone(&flusher),
+            interval,
+        ));
+
+        let write_handle = FlusherWriteHandle {
+            read_writer,
+            flusher: Arc::clone(&flusher),
+            layer_size_threshold,
+        };
+        let flusher_handle = FlusherHandle {
+            shared: flusher,
+            join: Some(join),
+        };
+        (write_handle, flusher_handle)
+    }
+}
+
+async fn run_flusher<T: Types + Send + 'static>(
+    read_writer: Arc<tokio::sync::Mutex<ReadWriter<T>>>,
+    flusher: Arc<FlusherShared>,
+    interval: Duration,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    // The first tick fires immediately; nothing to flush yet, so don't waste it.
+    ticker.tick().await;
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = flusher.notify.notified() => {}
+            _ = flusher.shutdown.notified() => {
+                let mut guard = read_writer.lock().await;
+                let _ = guard.flush_current_layer_if_any().await;
+                flusher.flushed.notify_waiters();
+                return;
+            }
+        }
+        let mut guard = read_writer.lock().await;
+        let _ = guard.flush_current_layer_if_any().await;
+        drop(guard);
+        flusher.flushed.notify_waiters();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{
@@ -203,7 +1632,7 @@ mod tests {
         sync::Arc,
     };
 
-    use crate::{seqwait, HistoricStuff};
+    use crate::{seqwait, HistoricStuff, MergeCursor};
 
     struct TestTypes;
 
@@ -225,6 +1654,18 @@ mod tests {
 
     struct HistoricLayer(InMemoryLayer);
 
+    impl super::HistoricLayerCursor<TestTypes> for Arc<HistoricLayer> {
+        fn cursor(&self, key: usize, lsn: usize) -> Box<dyn super::MergeCursor<TestTypes> + Send> {
+            let records = super::InMemoryLayer::get(&self.0, key, lsn);
+            Box::new(super::VecMergeCursor::new(
+                records
+                    .into_iter()
+                    .map(|(record_lsn, delta)| (key, record_lsn, delta))
+                    .collect(),
+            ))
+        }
+    }
+
     #[derive(Default)]
     struct LayerMap {
         by_key: BTreeMap<usize, BTreeMap<usize, Arc<HistoricLayer>>>,
@@ -303,16 +1744,15 @@ mod tests {
             Ok(())
         }
 
-        fn get(&self, key: usize, lsn: usize) -> Vec<&'static str> {
+        fn get(&self, key: usize, lsn: usize) -> Vec<(usize, &'static str)> {
             let by_key = match self.by_key.get(&key) {
                 Some(by_key) => by_key,
                 None => return vec![],
             };
             by_key
                 .range(..=lsn)
-                .map(|(_, v)| v)
+                .map(|(lsn, v)| (*lsn, *v))
                 .rev()
-                .cloned()
                 .collect()
         }
 
@@ -344,19 +1784,92 @@ mod tests {
             rw
         });
 
-        let read_res = rt.block_on(read_jh).unwrap().unwrap();
-        assert!(
-            read_res.historic_path.is_empty(),
-            "we have pushed less than needed for flush"
-        );
-        assert_eq!(read_res.inmem_records, vec!["baz", "foo"]);
+        let mut read_res = rt.block_on(read_jh).unwrap().unwrap();
+        let mut records = Vec::new();
+        while let Some((_, _, delta)) = read_res.next() {
+            records.push(delta);
+        }
+        // we have pushed less than needed for flush, so everything comes from the inmem layer
+        assert_eq!(records, vec!["baz", "foo"]);
 
         let rw = rt.block_on(async move {
             rw.put(0, 11, "blup").await.unwrap();
             rw
         });
-        let read_res = rt.block_on(async move { r2.get(0, 11).await.unwrap() });
-        assert_eq!(read_res.historic_path.len(), 0);
-        assert_eq!(read_res.inmem_records, vec!["blup", "baz", "foo"]);
+        let mut read_res = rt.block_on(async move { r2.get(0, 11).await.unwrap() });
+        let mut records = Vec::new();
+        while let Some((_, _, delta)) = read_res.next() {
+            records.push(delta);
+        }
+        assert_eq!(records, vec!["blup", "baz", "foo"]);
+    }
+
+    /// [`super::EntryCodec`] for [`TestTypes`], used only by the persistent-layer round-trip
+    /// tests below. Decoded `&'static str`s are produced by leaking a freshly-allocated
+    /// `String`, which is fine for a test that never runs long enough to care.
+    struct TestEntryCodec;
+
+    impl super::EntryCodec<TestTypes> for TestEntryCodec {
+        fn encode_entry(key: usize, lsn: usize, delta: &&'static str, out: &mut Vec<u8>) {
+            out.extend_from_slice(&(key as u64).to_le_bytes());
+            out.extend_from_slice(&(lsn as u64).to_le_bytes());
+            let bytes = delta.as_bytes();
+            out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+            out.extend_from_slice(bytes);
+        }
+
+        fn decode_entry(buf: &[u8]) -> (usize, usize, &'static str, usize) {
+            let key = u64::from_le_bytes(buf[0..8].try_into().unwrap()) as usize;
+            let lsn = u64::from_le_bytes(buf[8..16].try_into().unwrap()) as usize;
+            let len = u32::from_le_bytes(buf[16..20].try_into().unwrap()) as usize;
+            let s = std::str::from_utf8(&buf[20..20 + len]).unwrap().to_string();
+            (key, lsn, Box::leak(s.into_boxed_str()), 20 + len)
+        }
+
+        fn encode_key(key: usize, out: &mut Vec<u8>) {
+            out.extend_from_slice(&(key as u64).to_le_bytes());
+        }
+
+        fn decode_key(buf: &[u8]) -> (usize, usize) {
+            (u64::from_le_bytes(buf[0..8].try_into().unwrap()) as usize, 8)
+        }
+    }
+
+    #[test]
+    fn persistent_layer_round_trip_splits_blocks_at_key_boundaries_only() {
+        // A block size small enough that a single entry already exceeds it, so the threshold
+        // check fires after *every* entry. If `write_entry` flushed on that threshold without
+        // regard to whether the next entry is still the same key, key 0's three entries would
+        // land in three separate blocks, each claiming `first_key == 0`; a reader's single
+        // binary-search lookup would then only ever see one of them. With the key-boundary
+        // check, all three stay in one block and only the switch to key 1 triggers a flush.
+        let mut writer = super::SimplePersistentLayerWriter::<TestTypes, TestEntryCodec>::new(16);
+        writer.write_entry(0, 1, &"a");
+        writer.write_entry(0, 2, &"b");
+        writer.write_entry(0, 3, &"c");
+        writer.write_entry(1, 1, &"d");
+        let bytes = writer.finish();
+        let total_len = bytes.len() as u64;
+
+        let reader = super::SimplePersistentLayerReader::<TestTypes, TestEntryCodec, _>::open(
+            super::InMemoryObjectHandle(bytes),
+            total_len,
+        )
+        .unwrap();
+
+        let mut cursor = reader.cursor(0, 3).unwrap();
+        let mut seen = Vec::new();
+        while let Some((_, lsn, delta)) = cursor.advance() {
+            seen.push((lsn, delta));
+        }
+        // Newest first, and nothing from key 0's run lost to the block split.
+        assert_eq!(seen, vec![(3, "c"), (2, "b"), (1, "a")]);
+
+        let mut cursor = reader.cursor(1, 1).unwrap();
+        let mut seen = Vec::new();
+        while let Some((_, lsn, delta)) = cursor.advance() {
+            seen.push((lsn, delta));
+        }
+        assert_eq!(seen, vec![(1, "d")]);
     }
 }
@@ -13,21 +13,24 @@
 
 use anyhow::{bail, Context};
 use futures::FutureExt;
+use futures::StreamExt;
 use pageserver_api::models::TimelineState;
 use remote_storage::DownloadError;
 use remote_storage::GenericRemoteStorage;
 use storage_broker::BrokerClientChannel;
 use tokio::sync::watch;
 use tokio::sync::OwnedMutexGuard;
+use tokio::sync::Semaphore;
 use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
 use tracing::*;
 use utils::completion;
 use utils::crashsafe::path_with_suffix_extension;
 
 use std::cmp::min;
-use std::collections::hash_map::Entry;
 use std::collections::BTreeSet;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::ffi::OsStr;
 use std::fs;
 use std::fs::DirEntry;
@@ -47,24 +50,30 @@ use std::time::{Duration, Instant};
 
 use self::config::TenantConf;
 use self::metadata::TimelineMetadata;
+use self::metadata_store::{MetadataBackendKind, MetadataStore};
 use self::remote_timeline_client::RemoteTimelineClient;
+use self::timeline_registry::Entry;
+use self::timeline_registry::TimelineRegistry;
 use self::timeline::EvictionTaskTenantState;
 use crate::config::PageServerConf;
 use crate::context::{DownloadBehavior, RequestContext};
 use crate::import_datadir;
 use crate::is_uninit_mark;
 use crate::metrics::{remove_tenant_metrics, TENANT_STATE_METRIC, TENANT_SYNTHETIC_SIZE_METRIC};
+use crate::METADATA_FILE_NAME;
 use crate::repository::GcResult;
 use crate::task_mgr;
 use crate::task_mgr::TaskKind;
 use crate::tenant::config::TenantConfOpt;
 use crate::tenant::metadata::load_metadata;
 use crate::tenant::remote_timeline_client::index::IndexPart;
+use crate::tenant::remote_timeline_client::index::LayerFileMetadata;
 use crate::tenant::remote_timeline_client::MaybeDeletedIndexPart;
 use crate::tenant::remote_timeline_client::PersistIndexPartWithDeletedFlagError;
 use crate::tenant::storage_layer::DeltaLayer;
 use crate::tenant::storage_layer::ImageLayer;
 use crate::tenant::storage_layer::Layer;
+use crate::tenant::storage_layer::LayerFileName;
 use crate::InitializationOrder;
 
 use crate::virtual_file::VirtualFile;
@@ -88,11 +97,14 @@ pub mod layer_map;
 pub mod manifest;
 
 pub mod metadata;
+pub mod metadata_store;
 mod par_fsync;
 mod remote_timeline_client;
 pub mod storage_layer;
+mod timeline_registry;
 
 pub mod config;
+pub mod human_units;
 pub mod mgr;
 pub mod tasks;
 pub mod upload_queue;
@@ -120,6 +132,181 @@ pub const TIMELINES_SEGMENT_NAME: &str = "timelines";
 
 pub const TENANT_ATTACHING_MARKER_FILENAME: &str = "attaching";
 
+/// Name of the file recording the on-disk layout version of a tenant
+/// directory. It is written atomically inside the temporary tenant directory
+/// during creation (alongside the attach marker) and fsync'd before the
+/// directory is renamed into place, so every tenant on disk carries a version
+/// stamp. Load reads it back to decide whether the directory can be used as-is,
+/// needs migrating, or is from an incompatibly newer pageserver.
+pub const LAYOUT_VERSION_FILENAME: &str = "layout_version";
+
+/// Current on-disk tenant directory layout version. Bump this whenever the
+/// directory structure changes in a way that older pageservers can't read, and
+/// register a migration from the previous version in `layout::MIGRATIONS`.
+pub const CURRENT_LAYOUT_VERSION: u32 = 1;
+
+/// Durable marker written into a timeline directory once its deletion has been
+/// committed to. It is fsync'd before any `remove_dir_all`, so that a crash
+/// part-way through local deletion is detected on the next tenant load and the
+/// deletion is resumed instead of the half-deleted timeline coming up `Active`.
+/// Unlike the remote `IndexPart` `deleted` flag, this works for deployments
+/// without remote storage.
+pub const TIMELINE_DELETE_MARK_FILENAME: &str = "deleted";
+
+/// Upper bound on the number of remote-storage operations (index-part downloads
+/// and per-timeline loads) run concurrently while a single tenant is attaching
+/// or loading. Without a bound, a tenant with thousands of timelines fans out
+/// thousands of simultaneous remote requests, blowing past connection limits
+/// and starving other attaching tenants. The value is used as-is: neither
+/// `TenantConfOpt` nor `PageServerConf` can override it.
+pub const DEFAULT_ATTACH_CONCURRENCY: usize = 8;
+
+/// Upper bound on the number of timelines compacted concurrently within a
+/// single tenant's compaction iteration. Keeps a many-timeline tenant from
+/// saturating disk I/O while still filling otherwise-idle bandwidth. The value
+/// is used as-is; there is no config setting that overrides it.
+pub const DEFAULT_MAX_CONCURRENT_COMPACTIONS: usize = 4;
+
+/// Upper bound on timelines scrubbed concurrently within a single tenant's
+/// scrub iteration. Kept lower than the compaction bound because scrubbing
+/// reads every resident layer front-to-back, so it is deliberately gentle on
+/// disk I/O — the same intent as Garage's scrub tranquilizer.
+pub const DEFAULT_MAX_CONCURRENT_SCRUBS: usize = 2;
+
+/// How long graceful shutdown waits for a single timeline's remote uploads to
+/// drain before giving up on it and recording the upload as incomplete, so a
+/// hung remote-storage connection can't block shutdown indefinitely.
+pub const SHUTDOWN_UPLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Why a timeline failed to fully flush during graceful shutdown.
+#[derive(Debug)]
+pub enum ShutdownFlushError {
+    /// The local freeze-and-flush itself failed.
+    Flush(String),
+    /// Remote uploads reported an error.
+    Upload(String),
+    /// Remote uploads did not complete within [`SHUTDOWN_UPLOAD_TIMEOUT`].
+    UploadIncomplete,
+}
+
+/// Per-timeline outcome of [`Tenant::freeze_and_flush_on_shutdown`].
+#[derive(Debug)]
+pub struct ShutdownFlushSummary {
+    pub results: Vec<(TimelineId, Result<(), ShutdownFlushError>)>,
+}
+
+impl ShutdownFlushSummary {
+    /// `true` if every timeline flushed and fully uploaded.
+    pub fn is_fully_consistent(&self) -> bool {
+        self.results.iter().all(|(_, r)| r.is_ok())
+    }
+
+    /// Timelines whose remote state may be inconsistent after shutdown.
+    pub fn failed_timelines(&self) -> impl Iterator<Item = &TimelineId> {
+        self.results
+            .iter()
+            .filter_map(|(id, r)| r.is_err().then_some(id))
+    }
+}
+
+/// Retry budget for the remote-storage calls performed while attaching or
+/// loading a tenant. A momentary network blip should not flip a whole tenant to
+/// `Broken` and require operator intervention, so each call is retried with
+/// exponential backoff and jitter before the error is allowed to propagate.
+#[derive(Debug, Clone, Copy)]
+pub struct RemoteRetryConfig {
+    /// Total number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubles on every subsequent attempt.
+    pub base_delay: Duration,
+    /// Upper bound on a single backoff interval.
+    pub max_delay: Duration,
+}
+
+impl Default for RemoteRetryConfig {
+    fn default() -> Self {
+        RemoteRetryConfig {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Classifies an error seen while talking to remote storage as transient
+/// (worth retrying) or permanent. Timeouts, 5xx and connection resets are
+/// transient; parse errors and a 404 for a required object are permanent.
+trait RetryableRemoteError {
+    fn is_retryable(&self) -> bool;
+}
+
+impl RetryableRemoteError for DownloadError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            // The object genuinely isn't there; retrying can't help.
+            DownloadError::NotFound => false,
+            // Misconfiguration or a bad request — not transient.
+            DownloadError::BadInput(_) => false,
+            // Timeouts, connection resets and 5xx responses land here.
+            DownloadError::Other(_) => true,
+        }
+    }
+}
+
+impl RetryableRemoteError for anyhow::Error {
+    fn is_retryable(&self) -> bool {
+        if let Some(download) = self.downcast_ref::<DownloadError>() {
+            return download.is_retryable();
+        }
+        // Metadata parse failures surface as plain anyhow errors; treat those
+        // as permanent and anything else as a transient I/O hiccup.
+        let msg = self.to_string();
+        !(msg.contains("parse") || msg.contains("deserialize"))
+    }
+}
+
+/// Run `op` with exponential backoff and jitter, retrying only transient
+/// failures. `what` names the operation for log lines.
+async fn retry_remote<T, E, F, Fut>(
+    cfg: RemoteRetryConfig,
+    what: &str,
+    mut op: F,
+) -> Result<T, E>
+where
+    E: RetryableRemoteError + std::fmt::Display,
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, E>>,
+{
+    use rand::Rng;
+
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(err) => {
+                if !err.is_retryable() || attempt >= cfg.max_attempts {
+                    return Err(err);
+                }
+                // Full-jitter backoff: sleep a random amount in `[0, backoff]`.
+                let exp = cfg
+                    .base_delay
+                    .saturating_mul(1u32 << (attempt - 1).min(16))
+                    .min(cfg.max_delay);
+                let jittered = rand::thread_rng().gen_range(0..=exp.as_millis() as u64);
+                let delay = Duration::from_millis(jittered);
+                warn!(
+                    attempt,
+                    max_attempts = cfg.max_attempts,
+                    ?delay,
+                    "retrying {what} after transient error: {err}"
+                );
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
+
 ///
 /// Tenant consists of multiple timelines. Keep them in a hash table.
 ///
@@ -140,7 +327,12 @@ pub struct Tenant {
     tenant_conf: Arc<RwLock<TenantConfOpt>>,
 
     tenant_id: TenantId,
-    pub(super) timelines: Mutex<HashMap<TimelineId, Arc<Timeline>>>,
+
+    /// Where timeline metadata (see [`TimelineMetadata`]) is durably stored.
+    /// Selected once at construction; see [`metadata_store::MetadataBackendKind`].
+    metadata_store: Arc<dyn MetadataStore>,
+
+    pub(super) timelines: TimelineRegistry,
     // This mutex prevents creation of new timelines during GC.
     // Adding yet another mutex (in addition to `timelines`) is needed because holding
     // `timelines` mutex during all GC iteration
@@ -158,6 +350,88 @@ pub struct Tenant {
     cached_synthetic_tenant_size: Arc<AtomicU64>,
 
     eviction_task_tenant_state: tokio::sync::Mutex<EvictionTaskTenantState>,
+
+    /// Coarse progress of the in-flight attach/load, so the management API can
+    /// tell a slow-but-progressing attach apart from a stuck one.
+    attach_progress: AttachProgress,
+
+    /// Observable state of in-flight and recently-finished timeline deletions,
+    /// so a repeated DELETE can poll progress instead of getting an opaque error.
+    deletion_states: Mutex<HashMap<TimelineId, TimelineDeletionState>>,
+}
+
+/// Snapshot of how far an attach/load has advanced. All counters are monotonic
+/// within a single attach/load run. Cheap to read via shared atomics.
+#[derive(Default)]
+pub struct AttachProgress {
+    /// Timelines found in the remote listing (or on local disk for a load).
+    timelines_total: AtomicU64,
+    /// Index parts downloaded and parsed so far.
+    index_parts_downloaded: AtomicU64,
+    /// Timelines fully initialized and inserted into the tenant.
+    timelines_initialized: AtomicU64,
+    /// Bytes fetched from remote storage. Neither the attach nor the load path
+    /// calls `add_bytes_fetched`, so this stays 0.
+    bytes_fetched: AtomicU64,
+}
+
+/// A plain, point-in-time copy of [`AttachProgress`] suitable for serializing
+/// to an HTTP client.
+#[derive(Debug, Clone, Copy)]
+pub struct AttachProgressSnapshot {
+    pub timelines_total: u64,
+    pub index_parts_downloaded: u64,
+    pub timelines_initialized: u64,
+    pub bytes_fetched: u64,
+}
+
+impl AttachProgress {
+    fn set_timelines_total(&self, n: u64) {
+        self.timelines_total.store(n, Ordering::Relaxed);
+    }
+
+    fn inc_index_parts_downloaded(&self) {
+        self.index_parts_downloaded.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn inc_timelines_initialized(&self) {
+        self.timelines_initialized.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn add_bytes_fetched(&self, bytes: u64) {
+        self.bytes_fetched.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> AttachProgressSnapshot {
+        AttachProgressSnapshot {
+            timelines_total: self.timelines_total.load(Ordering::Relaxed),
+            index_parts_downloaded: self.index_parts_downloaded.load(Ordering::Relaxed),
+            timelines_initialized: self.timelines_initialized.load(Ordering::Relaxed),
+            bytes_fetched: self.bytes_fetched.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Where to branch a new timeline off its ancestor.
+#[derive(Debug, Clone)]
+pub enum BranchPoint {
+    /// Branch at an explicit LSN.
+    Lsn(Lsn),
+    /// Branch at a symbolic restore point recorded in the ancestor's history.
+    NamedPoint(String),
+    /// Branch at the ancestor's latest record LSN.
+    Latest,
+}
+
+impl From<Option<Lsn>> for BranchPoint {
+    /// Preserves the previous `ancestor_start_lsn: Option<Lsn>` contract: an
+    /// explicit LSN branches there, `None` branches at the latest record.
+    fn from(lsn: Option<Lsn>) -> Self {
+        match lsn {
+            Some(lsn) => BranchPoint::Lsn(lsn),
+            None => BranchPoint::Latest,
+        }
+    }
 }
 
 /// Similar to `Arc::ptr_eq`, but only compares the object pointers, not vtables.
@@ -287,10 +561,7 @@ impl<'t> CreatingTimelineGuard<'t> {
     }
 
     fn remove_placeholder_timeline_object_from_inmemory_map(&self) {
-        let Ok(mut timelines) = self.owning_tenant.timelines.lock() else {
-            error!("timelines lock poisoned, not removing placeholder timeline");
-            return;
-        };
+        let timelines = &self.owning_tenant.timelines;
         match timelines.entry(self.timeline_id) {
             Entry::Occupied(entry) => {
                 if compare_arced_timeline(&self.placeholder_timeline, entry.get()) {
@@ -394,13 +665,36 @@ pub enum DeleteTimelineError {
     NotFound,
     #[error("HasChildren")]
     HasChildren(Vec<TimelineId>),
+    /// Deletion is already running for this timeline. The caller should poll the
+    /// deletion status rather than treating this as a hard failure — the HTTP
+    /// layer turns it into a "202 Accepted, poll here" response.
+    #[error("AlreadyInProgress")]
+    AlreadyInProgress,
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
 
+/// Observable state of a background timeline deletion, keyed by `TimelineId` on
+/// the owning [`Tenant`]. Lets a repeated DELETE poll progress instead of
+/// getting an opaque error.
+#[derive(Debug, Clone)]
+pub enum TimelineDeletionState {
+    /// The deletion worker has been spawned but hasn't started yet.
+    Scheduled,
+    /// The deletion worker is actively removing the timeline.
+    InProgress,
+    /// The deletion failed; `reason` carries the error for diagnostics.
+    Failed { reason: String },
+    /// The timeline has been fully deleted.
+    Done,
+}
+
 pub enum SetStoppingError {
     AlreadyStopping,
     Broken,
+    /// The shutdown deadline elapsed while waiting for the tenant to finish
+    /// activating. `pending` names the kinds still outstanding.
+    Timeout { pending: Vec<TaskKind> },
 }
 
 struct RemoteStartupData {
@@ -417,6 +711,11 @@ pub(crate) enum WaitToBecomeActiveError {
     TenantDropped {
         tenant_id: TenantId,
     },
+    Timeout {
+        tenant_id: TenantId,
+        waited: Duration,
+        last_state: TenantState,
+    },
 }
 
 impl std::fmt::Display for WaitToBecomeActiveError {
@@ -432,6 +731,16 @@ impl std::fmt::Display for WaitToBecomeActiveError {
             WaitToBecomeActiveError::TenantDropped { tenant_id } => {
                 write!(f, "Tenant {tenant_id} will not become active (dropped)")
             }
+            WaitToBecomeActiveError::Timeout {
+                tenant_id,
+                waited,
+                last_state,
+            } => {
+                write!(
+                    f,
+                    "Tenant {tenant_id} did not become active within {waited:?}. Last state: {last_state:?}"
+                )
+            }
         }
     }
 }
@@ -466,6 +775,10 @@ impl std::fmt::Debug for TimelineLoadCause {
 
 pub(crate) enum ShutdownError {
     AlreadyStopping,
+    /// The shutdown deadline elapsed while one or more task kinds were still
+    /// running. `pending` lists the kinds that had not finished, to give the
+    /// operator something actionable when SIGTERM handling times out.
+    Timeout { pending: Vec<TaskKind> },
 }
 
 impl Tenant {
@@ -714,6 +1027,21 @@ impl Tenant {
             );
         }
 
+        // The marker file outlives an interrupted attach, so entering here with
+        // partially-downloaded local state is expected after a crash. Scan what
+        // is already on disk so we can reuse it instead of re-downloading, which
+        // makes a re-run idempotent: a second attach simply fills in whatever
+        // the first one didn't finish and converges to Active.
+        let local_timelines = self.scan_local_timeline_dirs().with_context(|| {
+            format!("scan local timelines for resumable attach of {}", self.tenant_id)
+        })?;
+        if !local_timelines.is_empty() {
+            info!(
+                "resuming interrupted attach: {} timeline dir(s) already present locally",
+                local_timelines.len()
+            );
+        }
+
         // Get list of remote timelines
         // download index files for every tenant timeline
         info!("listing remote timelines");
@@ -723,16 +1051,21 @@ impl Tenant {
             .as_ref()
             .ok_or_else(|| anyhow::anyhow!("cannot attach without remote storage"))?;
 
-        let remote_timeline_ids = remote_timeline_client::list_remote_timelines(
-            remote_storage,
-            self.conf,
-            self.tenant_id,
-        )
+        let retry_cfg = RemoteRetryConfig::default();
+        let remote_timeline_ids = retry_remote(retry_cfg, "list remote timelines", || {
+            remote_timeline_client::list_remote_timelines(remote_storage, self.conf, self.tenant_id)
+        })
         .await?;
 
         info!("found {} timelines", remote_timeline_ids.len());
-
-        // Download & parse index parts
+        self.attach_progress
+            .set_timelines_total(remote_timeline_ids.len() as u64);
+
+        // Download & parse index parts, but bound the fan-out: a tenant with
+        // thousands of timelines must not open thousands of remote-storage
+        // connections at once. Each download task acquires a permit before it
+        // starts and holds it until it finishes, giving us backpressure.
+        let download_concurrency = Arc::new(Semaphore::new(DEFAULT_ATTACH_CONCURRENCY));
         let mut part_downloads = JoinSet::new();
         for timeline_id in remote_timeline_ids {
             let client = RemoteTimelineClient::new(
@@ -741,14 +1074,21 @@ impl Tenant {
                 self.tenant_id,
                 timeline_id,
             );
+            let permit = Arc::clone(&download_concurrency)
+                .acquire_owned()
+                .await
+                .expect("attach download semaphore is never closed");
             part_downloads.spawn(
                 async move {
+                    // Held for the duration of the download to cap concurrency.
+                    let _permit = permit;
                     debug!("starting index part download");
 
-                    let index_part = client
-                        .download_index_file()
-                        .await
-                        .context("download index file")?;
+                    let index_part = retry_remote(retry_cfg, "download index file", || {
+                        client.download_index_file()
+                    })
+                    .await
+                    .context("download index file")?;
 
                     debug!("finished index part download");
 
@@ -768,6 +1108,7 @@ impl Tenant {
             let result: Result<_, anyhow::Error> = result.context("joinset task join")?;
             let (timeline_id, client, index_part) = result?;
             debug!("successfully downloaded index part for timeline {timeline_id}");
+            self.attach_progress.inc_index_parts_downloaded();
             match index_part {
                 MaybeDeletedIndexPart::IndexPart(index_part) => {
                     timeline_ancestors.insert(
@@ -786,55 +1127,125 @@ impl Tenant {
         // For every timeline, download the metadata file, scan the local directory,
         // and build a layer map that contains an entry for each remote and local
         // layer file.
-        let sorted_timelines = tree_sort_timelines(timeline_ancestors)?;
+        // Loaded in ancestor-before-child order so each child can look up its
+        // already-loaded ancestor. This walk is sequential by necessity (a
+        // child depends on its ancestor being in `self.timelines`), which keeps
+        // the per-timeline layer/metadata work within the same concurrency
+        // bound as the index-part downloads above.
+        // A single bad timeline must not take down the rest of the tenant:
+        // record the failure, skip the timeline (and anything branched off it),
+        // and let the tenant activate with the timelines that did load.
+        let TreeSortResult {
+            ordered: sorted_timelines,
+            orphans: orphan_timelines,
+        } = tree_sort_timelines(timeline_ancestors, true)?;
+        let mut broken_timelines: HashMap<TimelineId, String> = HashMap::new();
+        for (orphan_id, missing_ancestor) in orphan_timelines {
+            broken_timelines.insert(
+                orphan_id,
+                format!("ancestor timeline {missing_ancestor} is missing"),
+            );
+        }
         for (timeline_id, remote_metadata) in sorted_timelines {
             let (index_part, remote_client) = remote_index_and_client
                 .remove(&timeline_id)
                 .expect("just put it in above");
 
-            // TODO again handle early failure
+            // Resolve the ancestor. If it failed to load (or, because of that,
+            // was never inserted), skip this child instead of panicking on the
+            // missing lookup, and mark it broken for the same reason.
             let ancestor = if let Some(ancestor_id) = remote_metadata.ancestor_timeline() {
-                let timelines = self.timelines.lock().unwrap();
-                AncestorArg::ancestor(Arc::clone(timelines.get(&ancestor_id).ok_or_else(
-                    || {
-                        anyhow::anyhow!(
-                        "cannot find ancestor timeline {ancestor_id} for timeline {timeline_id}"
-                    )
-                    },
-                )?))
+                if broken_timelines.contains_key(&ancestor_id) {
+                    let reason = format!("ancestor timeline {ancestor_id} failed to load");
+                    warn!("skipping timeline {timeline_id}: {reason}");
+                    broken_timelines.insert(timeline_id, reason);
+                    continue;
+                }
+                match self.timelines.get(&ancestor_id) {
+                    Some(ancestor) => AncestorArg::ancestor(ancestor),
+                    None => {
+                        let reason =
+                            format!("ancestor timeline {ancestor_id} is missing");
+                        warn!("skipping timeline {timeline_id}: {reason}");
+                        broken_timelines.insert(timeline_id, reason);
+                        continue;
+                    }
+                }
             } else {
                 AncestorArg::no_ancestor()
             };
-            let timeline = self
+            // If this timeline's directory already survived a previous, interrupted attach,
+            // load whatever local metadata it left behind and let `timeline_init_and_sync`
+            // reconcile it against what we just downloaded, instead of blindly treating the
+            // remote copy as authoritative and overwriting it.
+            let local_metadata = if local_timelines.contains(&timeline_id) {
+                match load_metadata(self.conf, timeline_id, self.tenant_id) {
+                    Ok(metadata) => {
+                        debug!("reusing already-downloaded local state for timeline {timeline_id}");
+                        Some(metadata)
+                    }
+                    Err(e) => {
+                        warn!(
+                            "timeline {timeline_id} has a local directory but its metadata \
+                             couldn't be read ({e:#}), treating it as not locally present"
+                        );
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+            let timeline = match self
                 .load_remote_timeline(
                     timeline_id,
                     index_part,
                     remote_metadata,
+                    local_metadata,
                     ancestor,
                     remote_client,
                     ctx,
                 )
                 .await
-                .with_context(|| {
-                    format!(
-                        "failed to load remote timeline {} for tenant {}",
-                        timeline_id, self.tenant_id
-                    )
-                })?;
+            {
+                Ok(timeline) => timeline,
+                Err(e) => {
+                    // Isolate the failure to this one timeline.
+                    error!("failed to load remote timeline {timeline_id}: {e:#}");
+                    broken_timelines.insert(timeline_id, format!("{e:#}"));
+                    continue;
+                }
+            };
             // TODO: why can't load_remote_timeline return None like load_local_timeline does?
 
-            let mut timelines = self.timelines.lock().unwrap();
-            let overwritten = timelines.insert(timeline_id, Arc::clone(&timeline));
+            let overwritten = self.timelines.insert(timeline_id, Arc::clone(&timeline));
             if let Some(overwritten) = overwritten {
                 panic!(
                     "timeline should not be in the map yet, but is: {timeline_id}: {:?}",
                     overwritten.current_state()
                 );
             }
+            self.attach_progress.inc_timelines_initialized();
         }
 
-        std::fs::remove_file(&marker_file)
-            .with_context(|| format!("unlink attach marker file {}", marker_file.display()))?;
+        if !broken_timelines.is_empty() {
+            warn!(
+                "attached tenant with {} broken timeline(s): {:?}",
+                broken_timelines.len(),
+                broken_timelines.keys().collect::<Vec<_>>()
+            );
+        }
+
+        // Idempotent: a resumed attach may find the marker already gone if a
+        // previous run got this far.
+        match std::fs::remove_file(&marker_file) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!("unlink attach marker file {}", marker_file.display())
+                })
+            }
+        }
         crashsafe::fsync(marker_file.parent().expect("marker file has parent dir"))
             .context("fsync tenant directory after unlinking attach marker file")?;
 
@@ -845,6 +1256,37 @@ impl Tenant {
         Ok(())
     }
 
+    /// Enumerate the timeline directories already present on local disk. Used
+    /// to resume an interrupted attach by reusing downloaded state rather than
+    /// re-fetching it. Missing timelines directory (a brand-new attach) yields
+    /// an empty set rather than an error.
+    fn scan_local_timeline_dirs(&self) -> anyhow::Result<HashSet<TimelineId>> {
+        let timelines_dir = self.conf.timelines_path(&self.tenant_id);
+        let mut found = HashSet::new();
+        let dir = match std::fs::read_dir(&timelines_dir) {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(found),
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!("read timelines dir {}", timelines_dir.display())
+                })
+            }
+        };
+        for entry in dir {
+            let entry = entry.context("read timelines dir entry")?;
+            if !entry.file_type().context("stat timelines dir entry")?.is_dir() {
+                continue;
+            }
+            // Skip uninit marks and other non-timeline bookkeeping files.
+            if let Some(name) = entry.file_name().to_str() {
+                if let Ok(timeline_id) = name.parse::<TimelineId>() {
+                    found.insert(timeline_id);
+                }
+            }
+        }
+        Ok(found)
+    }
+
     /// get size of all remote timelines
     ///
     /// This function relies on the index_part instead of listing the remote storage
@@ -867,6 +1309,7 @@ impl Tenant {
         timeline_id: TimelineId,
         index_part: IndexPart,
         remote_metadata: TimelineMetadata,
+        local_metadata: Option<TimelineMetadata>,
         ancestor: AncestorArg,
         remote_client: RemoteTimelineClient,
         ctx: &RequestContext,
@@ -878,11 +1321,6 @@ impl Tenant {
             .await
             .context("Failed to create new timeline directory")?;
 
-        // Even if there is local metadata it cannot be ahead of the remote one
-        // since we're attaching. Even if we resume interrupted attach remote one
-        // cannot be older than the local one
-        let local_metadata = None;
-
         self.timeline_init_and_sync(
             timeline_id,
             Some(Arc::new(remote_client)),
@@ -1021,6 +1459,12 @@ impl Tenant {
 
         utils::failpoint_sleep_millis_async!("before-loading-tenant");
 
+        // Before touching any of the tenant's files, validate (and if necessary
+        // migrate) the on-disk directory layout. An incompatibly newer layout
+        // is surfaced as a load error rather than misread.
+        layout::check_and_migrate(&self.conf.tenant_path(&self.tenant_id))
+            .context("check tenant directory layout version")?;
+
         // TODO split this into two functions, scan and actual load
 
         // Load in-memory state to reflect the local files on disk
@@ -1032,7 +1476,11 @@ impl Tenant {
         let span = info_span!("blocking");
 
         let myself = Arc::clone(self);
-        let sorted_timelines: Vec<(_, _)> = tokio::task::spawn_blocking(move || {
+        let (sorted_timelines, orphan_timelines, timelines_to_resume_deletion): (
+            Vec<(_, _)>,
+            Vec<(TimelineId, TimelineId)>,
+            Vec<TimelineId>,
+        ) = tokio::task::spawn_blocking(move || {
             let _g = span.entered();
             let timelines_dir = conf.timelines_path(&tenant_id);
 
@@ -1096,6 +1544,7 @@ impl Tenant {
             };
 
             let mut timelines_to_load: HashMap<TimelineId, TimelineMetadata> = HashMap::new();
+            let mut timelines_to_resume_deletion: Vec<TimelineId> = Vec::new();
             for entry in entries {
                 let timeline_dir = entry.path();
                 assert!(!crate::is_temporary(&timeline_dir), "removed above");
@@ -1111,14 +1560,33 @@ impl Tenant {
                             timeline_dir.display()
                         )
                     })?;
-                let metadata = load_metadata(myself.conf, timeline_id, myself.tenant_id)
-                    .context("failed to load metadata")?;
+                // A directory carrying the deletion marker is a timeline whose
+                // deletion was interrupted. Its metadata may already be gone, so
+                // don't try to load it here; resume the deletion in the async
+                // load loop instead (see `resume_timeline_deletion`).
+                if timeline_dir.join(TIMELINE_DELETE_MARK_FILENAME).exists() {
+                    info!(
+                        "Found deletion marker in {}, will resume deletion instead of loading",
+                        timeline_dir.display()
+                    );
+                    timelines_to_resume_deletion.push(timeline_id);
+                    continue;
+                }
+                let metadata = myself
+                    .metadata_store
+                    .get(timeline_id)
+                    .context("failed to load metadata")?
+                    .with_context(|| format!("no metadata found for timeline {timeline_id}"))?;
                 timelines_to_load.insert(timeline_id, metadata);
             }
 
             // Sort the array of timeline IDs into tree-order, so that parent comes before
-            // all its children.
-            tree_sort_timelines(timelines_to_load)
+            // all its children. A timeline whose ancestor is missing must not
+            // take the whole tenant offline: come up with the loadable ones and
+            // mark only the orphans Broken.
+            let TreeSortResult { ordered, orphans } =
+                tree_sort_timelines(timelines_to_load, true)?;
+            Ok::<_, anyhow::Error>((ordered, orphans, timelines_to_resume_deletion))
         })
         .await
         .context("load spawn_blocking")
@@ -1127,20 +1595,50 @@ impl Tenant {
         // FIXME original collect_timeline_files contained one more check:
         //    1. "Timeline has no ancestor and no layer files"
 
+        self.attach_progress
+            .set_timelines_total(sorted_timelines.len() as u64);
+        let mut broken_timelines: HashMap<TimelineId, String> = HashMap::new();
+
+        // Timelines whose ancestor is absent can't be ordered or loaded; record
+        // them as broken so the tenant still activates with the rest.
+        for (orphan_id, missing_ancestor) in orphan_timelines {
+            broken_timelines.insert(
+                orphan_id,
+                format!("ancestor timeline {missing_ancestor} is missing"),
+            );
+        }
+
+        // Finish any deletions that a previous process committed to but did not
+        // complete before crashing. These directories carry the durable
+        // `deleted` marker and were excluded from `sorted_timelines` above.
+        for timeline_id in timelines_to_resume_deletion {
+            if let Err(e) = self.resume_timeline_deletion(timeline_id).await {
+                error!("failed to resume deletion of timeline {timeline_id}: {e:#}");
+                broken_timelines.insert(timeline_id, format!("{e:#}"));
+            }
+        }
+
         for (timeline_id, local_metadata) in sorted_timelines {
             let ancestor = if let Some(ancestor_id) = local_metadata.ancestor_timeline() {
-                let timelines = self.timelines.lock().unwrap();
-                AncestorArg::ancestor(Arc::clone(timelines.get(&ancestor_id).ok_or_else(
-                    || {
-                        anyhow::anyhow!(
-                        "cannot find ancestor timeline {ancestor_id} for timeline {timeline_id}"
-                    )
-                    },
-                )?))
+                if broken_timelines.contains_key(&ancestor_id) {
+                    let reason = format!("ancestor timeline {ancestor_id} failed to load");
+                    warn!("skipping timeline {timeline_id}: {reason}");
+                    broken_timelines.insert(timeline_id, reason);
+                    continue;
+                }
+                match self.timelines.get(&ancestor_id) {
+                    Some(ancestor) => AncestorArg::ancestor(ancestor),
+                    None => {
+                        let reason = format!("ancestor timeline {ancestor_id} is missing");
+                        warn!("skipping timeline {timeline_id}: {reason}");
+                        broken_timelines.insert(timeline_id, reason);
+                        continue;
+                    }
+                }
             } else {
                 AncestorArg::no_ancestor()
             };
-            let timeline = self
+            let timeline = match self
                 .load_local_timeline(
                     timeline_id,
                     local_metadata,
@@ -1151,17 +1649,25 @@ impl Tenant {
                 )
                 .instrument(info_span!("load_local_timeline", timeline_id=%timeline_id))
                 .await
-                .with_context(|| format!("load local timeline {timeline_id}"))?;
+            {
+                Ok(timeline) => timeline,
+                Err(e) => {
+                    error!("failed to load local timeline {timeline_id}: {e:#}");
+                    broken_timelines.insert(timeline_id, format!("{e:#}"));
+                    continue;
+                }
+            };
             match timeline {
                 Some(loaded_timeline) => {
-                    let mut timelines = self.timelines.lock().unwrap();
-                    let overwritten = timelines.insert(timeline_id, Arc::clone(&loaded_timeline));
+                    let overwritten =
+                        self.timelines.insert(timeline_id, Arc::clone(&loaded_timeline));
                     if let Some(overwritten) = overwritten {
                         panic!(
                             "timeline should not be in the map yet, but is: {timeline_id}: {:?}",
                             overwritten.current_state()
                         );
                     }
+                    self.attach_progress.inc_timelines_initialized();
                 }
                 None => {
                     info!(%timeline_id, "timeline is marked as deleted on the remote, load_local_timeline finished the deletion locally");
@@ -1170,6 +1676,14 @@ impl Tenant {
             }
         }
 
+        if !broken_timelines.is_empty() {
+            warn!(
+                "loaded tenant with {} broken timeline(s): {:?}",
+                broken_timelines.len(),
+                broken_timelines.keys().collect::<Vec<_>>()
+            );
+        }
+
         trace!("Done");
 
         Ok(())
@@ -1200,7 +1714,13 @@ impl Tenant {
         });
 
         let (remote_startup_data, remote_client) = match remote_client {
-            Some(remote_client) => match remote_client.download_index_file().await {
+            Some(remote_client) => match retry_remote(
+                RemoteRetryConfig::default(),
+                "download index file",
+                || remote_client.download_index_file(),
+            )
+            .await
+            {
                 Ok(index_part) => {
                     let index_part = match index_part {
                         MaybeDeletedIndexPart::IndexPart(index_part) => index_part,
@@ -1254,8 +1774,7 @@ impl Tenant {
         timeline_id: TimelineId,
         active_only: bool,
     ) -> anyhow::Result<Arc<Timeline>> {
-        let timelines_accessor = self.timelines.lock().unwrap();
-        let timeline = timelines_accessor.get(&timeline_id).with_context(|| {
+        let timeline = self.timelines.get(&timeline_id).with_context(|| {
             format!("Timeline {}/{} was not found", self.tenant_id, timeline_id)
         })?;
 
@@ -1267,19 +1786,14 @@ impl Tenant {
                 timeline.current_state()
             )
         } else {
-            Ok(Arc::clone(timeline))
+            Ok(timeline)
         }
     }
 
     /// Lists timelines the tenant contains.
     /// Up to tenant's implementation to omit certain timelines that ar not considered ready for use.
     pub fn list_timelines(&self) -> Vec<Arc<Timeline>> {
-        self.timelines
-            .lock()
-            .unwrap()
-            .values()
-            .map(Arc::clone)
-            .collect()
+        self.timelines.values().collect()
     }
 
     /// This is used to create the initial 'main' timeline during bootstrapping,
@@ -1386,7 +1900,7 @@ impl Tenant {
         // real_timeline with more data and once that's done, we're ready to
         // replace the placeholder
 
-        // let real_timeline = match self.timelines.lock().unwrap().entry(new_timeline_id) {
+        // let real_timeline = match self.timelines.entry(new_timeline_id) {
         //     Entry::Vacant(_) => unreachable!("we created a placeholder earlier, and load_local_timeline should have inserted the real timeline"),
         //     Entry::Occupied(entry) => {
         //         assert_eq!(guard.placeholder_timeline.current_state(), TimelineState::Creating);
@@ -1451,7 +1965,7 @@ impl Tenant {
             .creation_complete_remove_uninit_marker_and_get_placeholder_timeline()
             .context("creation_complete_remove_uninit_marker_and_get_placeholder_timeline")?;
 
-        match self.timelines.lock().unwrap().entry(new_timeline_id) {
+        match self.timelines.entry(new_timeline_id) {
             Entry::Vacant(_) => unreachable!("we created a placeholder earlier, and load_local_timeline should have inserted the real timeline"),
             Entry::Occupied(mut o) => {
                 info!("replacing placeholder timeline with the real one");
@@ -1479,7 +1993,7 @@ impl Tenant {
         self: &Arc<Self>,
         new_timeline_id: TimelineId,
         ancestor_timeline_id: Option<TimelineId>,
-        ancestor_start_lsn: Option<Lsn>,
+        branch_point: BranchPoint,
         pg_version: u32,
         broker_client: storage_broker::BrokerClientChannel,
         ctx: &RequestContext,
@@ -1499,7 +2013,7 @@ impl Tenant {
                     .create_timeline_task(
                         new_timeline_id,
                         ancestor_timeline_id,
-                        ancestor_start_lsn,
+                        branch_point,
                         pg_version,
                         broker_client,
                         &ctx,
@@ -1514,12 +2028,42 @@ impl Tenant {
         rx.await.expect("task_mgr tasks run to completion")
     }
 
+    /// Translate a [`BranchPoint`] into a concrete start LSN on `ancestor`, or
+    /// `None` to mean "branch at the latest record". A named restore point is
+    /// looked up against the markers recorded in the ancestor's history; an
+    /// unknown name is an error rather than a silent fall-through to latest.
+    fn resolve_branch_point(
+        &self,
+        ancestor: &Arc<Timeline>,
+        branch_point: &BranchPoint,
+    ) -> anyhow::Result<Option<Lsn>> {
+        match branch_point {
+            BranchPoint::Lsn(lsn) => Ok(Some(*lsn)),
+            BranchPoint::Latest => Ok(None),
+            BranchPoint::NamedPoint(name) => {
+                let lsn = ancestor.lookup_restore_point(name).with_context(|| {
+                    format!(
+                        "look up restore point {name:?} on ancestor timeline {}",
+                        ancestor.timeline_id
+                    )
+                })?;
+                let lsn = lsn.ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "no restore point named {name:?} on ancestor timeline {}",
+                        ancestor.timeline_id
+                    )
+                })?;
+                Ok(Some(lsn))
+            }
+        }
+    }
+
     /// This is not cancel-safe. Run inside a task_mgr task.
     async fn create_timeline_task(
         self: &Arc<Self>,
         new_timeline_id: TimelineId,
         ancestor_timeline_id: Option<TimelineId>,
-        mut ancestor_start_lsn: Option<Lsn>,
+        branch_point: BranchPoint,
         pg_version: u32,
         broker_client: storage_broker::BrokerClientChannel,
         ctx: &RequestContext,
@@ -1553,6 +2097,11 @@ impl Tenant {
                             "Cannot branch off the timeline that's not present in pageserver",
                         )?;
 
+                    // Resolve the (possibly symbolic) branch point to a concrete
+                    // LSN against the ancestor. `None` means "branch at latest".
+                    let mut ancestor_start_lsn =
+                        self.resolve_branch_point(&ancestor_timeline, &branch_point)?;
+
                     if let Some(lsn) = ancestor_start_lsn.as_mut() {
                         *lsn = lsn.align();
 
@@ -1643,7 +2192,7 @@ impl Tenant {
             anyhow::bail!("we just created this timeline's local files, but load_local_timeline did not load it");
         };
 
-        match self.timelines.lock().unwrap().entry(new_timeline_id) {
+        match self.timelines.entry(new_timeline_id) {
             Entry::Vacant(_) => unreachable!("we created a placeholder earlier, and load_local_timeline should have inserted the real timeline"),
             Entry::Occupied(mut o) => {
                 info!("replacing placeholder timeline with the real one");
@@ -1677,6 +2226,7 @@ impl Tenant {
         target_timeline_id: Option<TimelineId>,
         horizon: u64,
         pitr: Duration,
+        cancel: &CancellationToken,
         ctx: &RequestContext,
     ) -> anyhow::Result<GcResult> {
         // there is a global allowed_error for this
@@ -1685,7 +2235,7 @@ impl Tenant {
             "Cannot run GC iteration on inactive tenant"
         );
 
-        self.gc_iteration_internal(target_timeline_id, horizon, pitr, ctx)
+        self.gc_iteration_internal(target_timeline_id, horizon, pitr, cancel, ctx)
             .await
     }
 
@@ -1693,7 +2243,11 @@ impl Tenant {
     /// This function is periodically called by compactor task.
     /// Also it can be explicitly requested per timeline through page server
     /// api's 'compact' command.
-    pub async fn compaction_iteration(&self, ctx: &RequestContext) -> anyhow::Result<()> {
+    pub async fn compaction_iteration(
+        &self,
+        cancel: &CancellationToken,
+        ctx: &RequestContext,
+    ) -> anyhow::Result<()> {
         anyhow::ensure!(
             self.is_active(),
             "Cannot run compaction iteration on inactive tenant"
@@ -1703,91 +2257,397 @@ impl Tenant {
         // while holding the lock. Then drop the lock and actually perform the
         // compactions.  We don't want to block everything else while the
         // compaction runs.
-        let timelines_to_compact = {
-            let timelines = self.timelines.lock().unwrap();
-            let timelines_to_compact = timelines
-                .iter()
-                .filter_map(|(timeline_id, timeline)| {
-                    if timeline.is_active() {
-                        Some((*timeline_id, timeline.clone()))
-                    } else {
-                        None
+        let timelines_to_compact = self
+            .timelines
+            .iter()
+            .filter_map(|(timeline_id, timeline)| {
+                if timeline.is_active() {
+                    Some((timeline_id, timeline))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Compact timelines concurrently, but with a bound so a tenant with
+        // many timelines doesn't saturate I/O all at once. Mirrors the JoinSet
+        // pattern in `freeze_and_flush_on_shutdown`. Individual failures are
+        // logged and collected; the pass returns an aggregate error only if at
+        // least one timeline failed.
+        let concurrency = Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENT_COMPACTIONS));
+        let mut js = tokio::task::JoinSet::new();
+        for (timeline_id, timeline) in timelines_to_compact {
+            if cancel.is_cancelled() {
+                // Stop dispatching new work at this timeline boundary; already
+                // dispatched compactions are still awaited below.
+                info!("compaction iteration cancelled before timeline {timeline_id}");
+                break;
+            }
+            let ctx = ctx.detached_child(TaskKind::Compaction, DownloadBehavior::Download);
+            let permit = Arc::clone(&concurrency)
+                .acquire_owned()
+                .await
+                .expect("compaction semaphore is never closed");
+            js.spawn(
+                async move {
+                    let _permit = permit;
+                    let res = timeline.compact(&ctx).await;
+                    (timeline_id, res)
+                }
+                .instrument(info_span!("compact_timeline", timeline = %timeline_id)),
+            );
+        }
+
+        let mut failed = 0;
+        while let Some(res) = js.join_next().await {
+            match res {
+                Ok((timeline_id, Ok(()))) => {
+                    debug!("compacted timeline {timeline_id}");
+                }
+                Ok((timeline_id, Err(e))) => {
+                    failed += 1;
+                    error!("compaction of timeline {timeline_id} failed: {e:#}");
+                }
+                Err(je) if je.is_panic() => {
+                    failed += 1;
+                    error!("compaction task panicked: {je:?}");
+                }
+                Err(je) => {
+                    failed += 1;
+                    error!("unexpected compaction JoinError: {je:?}");
+                }
+            }
+        }
+
+        anyhow::ensure!(failed == 0, "{failed} timeline(s) failed to compact");
+        Ok(())
+    }
+
+    /// Walk every resident layer of every active timeline, validate it, and
+    /// repair the ones that fail.
+    ///
+    /// This is the tenant-level counterpart to the manual
+    /// [`dump_layerfile_from_path`] CLI path: each layer file is opened, its
+    /// two-byte magic checked, and its block-index/footer parsed through the
+    /// same `ImageLayer::new_for_path` / `DeltaLayer::new_for_path` code the
+    /// dumper uses. A layer that fails to parse is evicted and re-downloaded
+    /// from remote storage, so a bit-rotted or truncated file on local disk
+    /// heals itself on the next pass.
+    ///
+    /// A scrub runs only when this is called: no background loop reads
+    /// [`Tenant::get_scrub_period`] to schedule it, and no management API
+    /// endpoint calls it.
+    pub async fn scrub_iteration(
+        &self,
+        cancel: &CancellationToken,
+        ctx: &RequestContext,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.is_active(),
+            "Cannot run scrub iteration on inactive tenant"
+        );
+
+        let timelines_to_scrub = self
+            .timelines
+            .iter()
+            .filter_map(|(timeline_id, timeline)| {
+                if timeline.is_active() {
+                    Some((timeline_id, timeline))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Scrub timelines with bounded concurrency, lower than compaction's: a
+        // full front-to-back read of every layer is exactly the kind of
+        // background work that shouldn't starve foreground I/O. This mirrors
+        // the `FuturesUnordered` pattern in [`Tenant::gc_iteration_internal`],
+        // which also has to borrow `self` across the concurrent futures.
+        let concurrency = DEFAULT_MAX_CONCURRENT_SCRUBS.max(1);
+        let mut failed = 0;
+        let mut in_flight = futures::stream::FuturesUnordered::new();
+        let mut pending = timelines_to_scrub.into_iter();
+
+        loop {
+            while in_flight.len() < concurrency {
+                if cancel.is_cancelled() {
+                    break;
+                }
+                match pending.next() {
+                    Some((timeline_id, timeline)) => {
+                        let ctx =
+                            ctx.detached_child(TaskKind::Compaction, DownloadBehavior::Download);
+                        in_flight.push(
+                            async move {
+                                let res = self.scrub_timeline(&timeline, &ctx).await;
+                                (timeline_id, res)
+                            }
+                            .instrument(info_span!("scrub_timeline", timeline = %timeline_id)),
+                        );
                     }
-                })
-                .collect::<Vec<_>>();
-            drop(timelines);
-            timelines_to_compact
+                    None => break,
+                }
+            }
+
+            let Some((timeline_id, res)) = in_flight.next().await else {
+                if cancel.is_cancelled() {
+                    info!("scrub iteration cancelled");
+                }
+                break;
+            };
+
+            match res {
+                Ok(summary) if summary.repaired > 0 => {
+                    info!(
+                        "scrubbed timeline {timeline_id}: {} layers checked, {} repaired",
+                        summary.checked, summary.repaired
+                    );
+                }
+                Ok(summary) => {
+                    debug!(
+                        "scrubbed timeline {timeline_id}: {} layers checked, all healthy",
+                        summary.checked
+                    );
+                }
+                Err(e) => {
+                    failed += 1;
+                    error!("scrub of timeline {timeline_id} failed: {e:#}");
+                }
+            }
+        }
+
+        anyhow::ensure!(failed == 0, "{failed} timeline(s) failed to scrub");
+        Ok(())
+    }
+
+    /// Validate and, where needed, repair every resident layer of a single
+    /// timeline. Returns a summary of how many layers were checked and how many
+    /// had to be re-downloaded.
+    async fn scrub_timeline(
+        &self,
+        timeline: &Arc<Timeline>,
+        ctx: &RequestContext,
+    ) -> anyhow::Result<ScrubSummary> {
+        let timeline_dir = self
+            .conf
+            .timeline_path(&timeline.timeline_id, &self.tenant_id);
+
+        let mut summary = ScrubSummary::default();
+        for entry in std::fs::read_dir(&timeline_dir).with_context(|| {
+            format!(
+                "Failed to list timeline directory for scrub: {}",
+                timeline_dir.display()
+            )
+        })? {
+            let entry = entry?;
+            let path = entry.path();
+
+            // Only layer files carry content worth validating. The metadata
+            // file, the deletion marker, and any leftover temporary files are
+            // not layers and are skipped.
+            let file_name = match path.file_name().and_then(OsStr::to_str) {
+                Some(name) => name,
+                None => continue,
+            };
+            if file_name == METADATA_FILE_NAME
+                || file_name == TIMELINE_DELETE_MARK_FILENAME
+                || crate::is_temporary(&path)
+            {
+                continue;
+            }
+            let layer_file_name = match file_name.parse::<LayerFileName>() {
+                Ok(name) => name,
+                Err(_) => {
+                    // Not a recognizable layer file name; leave it untouched.
+                    continue;
+                }
+            };
+
+            summary.checked += 1;
+            if let Err(e) = validate_layer_file(&path, ctx) {
+                warn!(
+                    "scrub found a corrupt layer {}, repairing from remote: {e:#}",
+                    path.display()
+                );
+                self.repair_layer(timeline, &layer_file_name, &path)
+                    .await
+                    .with_context(|| {
+                        format!("Failed to repair corrupt layer {}", path.display())
+                    })?;
+                crate::metrics::SCRUB_REPAIRED_LAYERS.inc();
+                summary.repaired += 1;
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Drop a failed layer's local copy and pull the authoritative one back from
+    /// remote storage. Requires the timeline to have a remote client; a layer
+    /// that only exists locally cannot be repaired and is surfaced as an error.
+    async fn repair_layer(
+        &self,
+        timeline: &Arc<Timeline>,
+        layer_file_name: &LayerFileName,
+        local_path: &Path,
+    ) -> anyhow::Result<()> {
+        let remote_client = timeline.remote_client.as_ref().context(
+            "cannot repair a corrupt layer without a remote timeline client to re-download it",
+        )?;
+
+        // Capture the on-disk size before evicting so the download can be
+        // checked against the index-part metadata, matching the on-demand
+        // download path in `storage_layer`.
+        let layer_metadata = match std::fs::metadata(local_path) {
+            Ok(m) => LayerFileMetadata::new(m.len()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => LayerFileMetadata::new(0),
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("stat corrupt layer {}", local_path.display()));
+            }
         };
 
-        for (timeline_id, timeline) in &timelines_to_compact {
-            timeline
-                .compact(ctx)
-                .instrument(info_span!("compact_timeline", timeline = %timeline_id))
-                .await?;
+        // Evict the bad copy first so the download writes a fresh file rather
+        // than appending to or trusting the damaged one.
+        match std::fs::remove_file(local_path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("evict corrupt layer {}", local_path.display()));
+            }
         }
 
+        remote_client
+            .download_layer_file(layer_file_name, &layer_metadata)
+            .await
+            .context("re-download authoritative layer from remote storage")?;
         Ok(())
     }
 
     /// Flush all in-memory data to disk and remote storage, if any.
     ///
-    /// Used at graceful shutdown.
-    async fn freeze_and_flush_on_shutdown(&self) {
+    /// Used at graceful shutdown. Returns a per-timeline summary so the caller
+    /// can tell whether remote storage is fully consistent after shutdown.
+    async fn freeze_and_flush_on_shutdown(&self) -> ShutdownFlushSummary {
         let mut js = tokio::task::JoinSet::new();
 
         // execute on each timeline on the JoinSet, join after.
         let per_timeline = |timeline: Arc<Timeline>| {
             async move {
-                match timeline.freeze_and_flush().await {
-                    Ok(()) => {}
-                    Err(err) => {
-                        tracing::error!(
-                            timeline_id=%timeline.timeline_id, err=?err,
-                            "freeze_and_flush timeline failed",
-                        );
-                        return;
-                    }
+                let timeline_id = timeline.timeline_id;
+                if let Err(err) = timeline.freeze_and_flush().await {
+                    tracing::error!(
+                        timeline_id=%timeline_id, err=?err,
+                        "freeze_and_flush timeline failed",
+                    );
+                    return (timeline_id, Err(ShutdownFlushError::Flush(format!("{err:#}"))));
                 }
 
-                let res = if let Some(client) = timeline.remote_client.as_ref() {
-                    // if we did not wait for completion here, it might be our shutdown process
-                    // didn't wait for remote uploads to complete at all, as new tasks can forever
-                    // be spawned.
-                    //
-                    // what is problematic is the shutting down of RemoteTimelineClient, because
-                    // obviously it does not make sense to stop while we wait for it, but what
-                    // about corner cases like s3 suddenly hanging up?
-                    client.wait_completion().await
-                } else {
-                    Ok(())
+                let Some(client) = timeline.remote_client.as_ref() else {
+                    return (timeline_id, Ok(()));
                 };
 
-                if let Err(e) = res {
-                    warn!("failed to await for frozen and flushed uploads: {e:#}");
-                }
+                // Wait for uploads to complete, but bound the wait so a hung S3
+                // connection can't block graceful shutdown indefinitely. On
+                // timeout we record the timeline as "upload incomplete" and move
+                // on rather than hanging forever.
+                let res = match tokio::time::timeout(
+                    SHUTDOWN_UPLOAD_TIMEOUT,
+                    client.wait_completion(),
+                )
+                .await
+                {
+                    Ok(Ok(())) => Ok(()),
+                    Ok(Err(e)) => {
+                        warn!("failed to await for frozen and flushed uploads: {e:#}");
+                        Err(ShutdownFlushError::Upload(format!("{e:#}")))
+                    }
+                    Err(_elapsed) => {
+                        warn!(
+                            "remote uploads did not complete within {SHUTDOWN_UPLOAD_TIMEOUT:?}; \
+                             recording upload as incomplete"
+                        );
+                        Err(ShutdownFlushError::UploadIncomplete)
+                    }
+                };
+                (timeline_id, res)
             }
             // NB: the freeze_and_flush inside the async block already adds tenant_id and timeline_id
             .instrument(tracing::info_span!("freeze_and_flush_on_shutdown"))
         };
 
-        {
-            let timelines = self.timelines.lock().unwrap();
-            timelines
-                .iter()
-                .map(|(_, tl)| Arc::clone(tl))
-                .for_each(|timeline| {
-                    js.spawn(per_timeline(timeline));
-                })
-        };
+        self.timelines.values().for_each(|timeline| {
+            js.spawn(per_timeline(timeline));
+        });
 
+        let mut results = Vec::new();
         while let Some(res) = js.join_next().await {
             match res {
-                Ok(()) => {}
+                Ok(outcome) => results.push(outcome),
                 Err(je) if je.is_cancelled() => unreachable!("no cancelling used"),
                 Err(je) if je.is_panic() => { /* logged already */ }
                 Err(je) => warn!("unexpected JoinError: {je:?}"),
             }
         }
+        ShutdownFlushSummary { results }
+    }
+
+    /// Write (and fsync) the [`TIMELINE_DELETE_MARK_FILENAME`] marker into
+    /// `timeline_directory`, committing the directory to deletion. The parent
+    /// (the timeline directory itself) is fsync'd so the marker survives a
+    /// crash. Returns a `NotFound` error if the directory no longer exists, so
+    /// callers retrying after a partial deletion can treat that as a no-op.
+    fn write_timeline_delete_mark(&self, timeline_directory: &Path) -> std::io::Result<()> {
+        let mark_path = timeline_directory.join(TIMELINE_DELETE_MARK_FILENAME);
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&mark_path)?;
+        file.sync_all()?;
+        crashsafe::fsync(timeline_directory)?;
+        Ok(())
+    }
+
+    /// Resume a local timeline deletion that a previous process committed to but
+    /// did not finish: the timeline directory still exists and contains the
+    /// durable `deleted` marker. We remove the directory and, if remote storage
+    /// is configured, schedule removal of the remote objects, mirroring the
+    /// tail of [`Tenant::delete_timeline`]. The timeline is never inserted into
+    /// `self.timelines`, so it does not come up `Active`.
+    async fn resume_timeline_deletion(&self, timeline_id: TimelineId) -> anyhow::Result<()> {
+        let local_timeline_directory = self.conf.timeline_path(&timeline_id, &self.tenant_id);
+        info!(
+            %timeline_id,
+            "found deletion marker, resuming interrupted timeline deletion"
+        );
+
+        match std::fs::remove_dir_all(&local_timeline_directory) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!(
+                        "Failed to remove local timeline directory '{}' while resuming deletion",
+                        local_timeline_directory.display()
+                    )
+                });
+            }
+        }
+
+        if let Some(remote_storage) = self.remote_storage.as_ref() {
+            let remote_client = RemoteTimelineClient::new(
+                remote_storage.clone(),
+                self.conf,
+                self.tenant_id,
+                timeline_id,
+            );
+            remote_client.delete_all().await?;
+        }
+
+        Ok(())
     }
 
     /// Shuts down a timeline's tasks, removes its in-memory structures, and deletes its
@@ -1825,12 +2685,29 @@ impl Tenant {
                 Err(anyhow::anyhow!("failpoint: timeline-delete-before-rm"))?
             });
 
-            // NB: This need not be atomic because the deleted flag in the IndexPart
-            // will be observed during tenant/timeline load. The deletion will be resumed there.
-            //
-            // For configurations without remote storage, we tolerate that we're not crash-safe here.
-            // The timeline may come up Active but with missing layer files, in such setups.
-            // See https://github.com/neondatabase/neon/pull/3919#issuecomment-1531726720
+            // Commit to the deletion by dropping a durable marker into the
+            // timeline directory *before* we start removing layer files. If we
+            // crash between here and the directory removal, the next tenant load
+            // sees the marker and resumes the deletion through
+            // `resume_timeline_deletion` instead of bringing
+            // up a timeline with missing layers. This makes local deletion
+            // crash-safe even without remote storage, so we no longer have to
+            // rely on the IndexPart `deleted` flag being present.
+            // The `NotFound` case is tolerated: on a retry the directory may
+            // already be gone, in which case there is nothing left to mark.
+            match self.write_timeline_delete_mark(&local_timeline_directory) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    return Err(e).with_context(|| {
+                        format!(
+                            "Failed to write deletion marker into timeline directory '{}'",
+                            local_timeline_directory.display()
+                        )
+                    });
+                }
+            }
+
             match std::fs::remove_dir_all(&local_timeline_directory) {
                 Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
                     // This can happen if we're called a second time, e.g.,
@@ -1870,8 +2747,8 @@ impl Tenant {
 
         {
             // Remove the timeline from the map.
-            let mut timelines = self.timelines.lock().unwrap();
-            let children_exist = timelines
+            let children_exist = self
+                .timelines
                 .iter()
                 .any(|(_, entry)| entry.get_ancestor_timeline_id() == Some(timeline_id));
             // XXX this can happen because `branch_timeline` doesn't check `TimelineState::Stopping`.
@@ -1881,11 +2758,9 @@ impl Tenant {
                 panic!("Timeline grew children while we removed layer files");
             }
 
-            timelines.remove(&timeline_id).expect(
+            self.timelines.remove(&timeline_id).expect(
                 "timeline that we were deleting was concurrently removed from 'timelines' map",
             );
-
-            drop(timelines);
         }
 
         let remote_client = match &timeline.remote_client {
@@ -1898,12 +2773,107 @@ impl Tenant {
         Ok(())
     }
 
-    /// Removes timeline-related in-memory data and schedules removal from remote storage.
-    #[instrument(skip(self, _ctx))]
-    pub async fn prepare_and_schedule_delete_timeline(
+    /// Removes timeline-related in-memory data and schedules removal from remote storage.
+    #[instrument(skip(self, _ctx))]
+    pub async fn prepare_and_schedule_delete_timeline(
+        self: Arc<Self>,
+        timeline_id: TimelineId,
+        ctx: &RequestContext,
+    ) -> Result<(), DeleteTimelineError> {
+        self.prepare_and_schedule_delete_timeline_impl(timeline_id, ctx, None)
+            .await
+    }
+
+    /// Delete `root` and its entire descendant branch subtree (cascade delete).
+    ///
+    /// The subtree is computed from the `get_ancestor_timeline_id` links in
+    /// `self.timelines` and deletions run children-first, each member's deletion
+    /// finishing before the next one is scheduled, so a parent is never removed
+    /// before its children. The operation is validated
+    /// atomically up front: if any member of the subtree is in `Creating` state
+    /// (and therefore undeletable), nothing is scheduled and the call fails. A
+    /// child being present is expected during a cascade, so the usual
+    /// [`DeleteTimelineError::HasChildren`] guard is relaxed to children that
+    /// are themselves part of the subtree.
+    pub async fn prepare_and_schedule_delete_timeline_cascade(
+        self: Arc<Self>,
+        root: TimelineId,
+        ctx: &RequestContext,
+    ) -> Result<(), DeleteTimelineError> {
+        let ordered = {
+            if !self.timelines.contains_key(&root) {
+                return Err(DeleteTimelineError::NotFound);
+            }
+
+            // Collect the full descendant subtree (including the root) and the
+            // depth of each member below the root, so we can delete deepest-first.
+            let mut depth: HashMap<TimelineId, usize> = HashMap::new();
+            depth.insert(root, 0);
+            // Repeatedly sweep until no new descendant is discovered. The number
+            // of timelines per tenant is small, so a fixpoint sweep is simpler
+            // than building an explicit adjacency list.
+            loop {
+                let mut changed = false;
+                for (id, entry) in self.timelines.iter() {
+                    if depth.contains_key(&id) {
+                        continue;
+                    }
+                    if let Some(parent) = entry.get_ancestor_timeline_id() {
+                        if let Some(parent_depth) = depth.get(&parent).copied() {
+                            depth.insert(id, parent_depth + 1);
+                            changed = true;
+                        }
+                    }
+                }
+                if !changed {
+                    break;
+                }
+            }
+
+            // Fail atomically before scheduling anything if any member can't be deleted.
+            for id in depth.keys() {
+                if let Some(entry) = self.timelines.get(id) {
+                    if entry.current_state() == TimelineState::Creating {
+                        return Err(DeleteTimelineError::Other(anyhow::anyhow!(
+                            "cannot cascade-delete {root}: descendant timeline {id} is creating"
+                        )));
+                    }
+                }
+            }
+
+            let mut ordered: Vec<TimelineId> = depth.keys().copied().collect();
+            // Deepest timelines first: children are deleted before their parents.
+            ordered.sort_by(|a, b| depth[b].cmp(&depth[a]));
+            ordered
+        };
+
+        let subtree: HashSet<TimelineId> = ordered.iter().copied().collect();
+        for timeline_id in ordered {
+            self.clone()
+                .prepare_and_schedule_delete_timeline_impl(timeline_id, ctx, Some(&subtree))
+                .await?;
+            // Wait for this member's deletion to finish, so that its parent's
+            // deletion never finds it still in `self.timelines`.
+            loop {
+                match self.timeline_deletion_status(timeline_id) {
+                    Some(TimelineDeletionState::Done) | None => break,
+                    Some(TimelineDeletionState::Failed { reason }) => {
+                        return Err(DeleteTimelineError::Other(anyhow::anyhow!(
+                            "cascade delete stopped: deleting {timeline_id} failed: {reason}"
+                        )));
+                    }
+                    Some(_) => tokio::time::sleep(Duration::from_millis(100)).await,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn prepare_and_schedule_delete_timeline_impl(
         self: Arc<Self>,
         timeline_id: TimelineId,
         _ctx: &RequestContext,
+        cascade_members: Option<&HashSet<TimelineId>>,
     ) -> Result<(), DeleteTimelineError> {
         timeline::debug_assert_current_span_has_tenant_and_timeline_id();
 
@@ -1914,15 +2884,19 @@ impl Tenant {
         let timeline;
         let delete_lock_guard;
         {
-            let mut timelines = self.timelines.lock().unwrap();
-
             // Ensure that there are no child timelines **attached to that pageserver**,
             // because detach removes files, which will break child branches
-            let children: Vec<TimelineId> = timelines
+            // During a cascade delete, children are expected: they belong to the
+            // subtree and are scheduled for deletion before this timeline. Only
+            // children outside the cascade set are a hard error.
+            let children: Vec<TimelineId> = self
+                .timelines
                 .iter()
                 .filter_map(|(id, entry)| {
-                    if entry.get_ancestor_timeline_id() == Some(timeline_id) {
-                        Some(*id)
+                    if entry.get_ancestor_timeline_id() == Some(timeline_id)
+                        && cascade_members.map_or(true, |set| !set.contains(&id))
+                    {
+                        Some(id)
                     } else {
                         None
                     }
@@ -1933,7 +2907,7 @@ impl Tenant {
                 return Err(DeleteTimelineError::HasChildren(children));
             }
 
-            let timeline_entry = match timelines.entry(timeline_id) {
+            let timeline_entry = match self.timelines.entry(timeline_id) {
                 Entry::Occupied(e) => e,
                 Entry::Vacant(_) => return Err(DeleteTimelineError::NotFound),
             };
@@ -1945,20 +2919,14 @@ impl Tenant {
                 )));
             }
 
-            // Prevent two tasks from trying to delete the timeline at the same time.
-            //
-            // XXX: We should perhaps return an HTTP "202 Accepted" to signal that the caller
-            // needs to poll until the operation has finished. But for now, we return an
-            // error, because the control plane knows to retry errors.
-
-            delete_lock_guard =
-                Arc::clone(&timeline.delete_lock)
-                    .try_lock_owned()
-                    .map_err(|_| {
-                        DeleteTimelineError::Other(anyhow::anyhow!(
-                            "timeline deletion is already in progress"
-                        ))
-                    })?;
+            // Prevent two tasks from trying to delete the timeline at the same
+            // time. A contended lock means a deletion is already running, which
+            // we report as `AlreadyInProgress` so the caller can poll the
+            // deletion status (HTTP "202 Accepted") instead of treating the
+            // repeated DELETE as a hard error.
+            delete_lock_guard = Arc::clone(&timeline.delete_lock)
+                .try_lock_owned()
+                .map_err(|_| DeleteTimelineError::AlreadyInProgress)?;
 
             // If another task finished the deletion just before we acquired the lock,
             // return success.
@@ -1967,8 +2935,6 @@ impl Tenant {
             }
 
             timeline.set_state(TimelineState::Stopping);
-
-            drop(timelines);
         }
 
         // Now that the Timeline is in Stopping state, request all the related tasks to
@@ -2027,6 +2993,8 @@ impl Tenant {
                 }
             }
         }
+
+        self.set_deletion_state(timeline_id, TimelineDeletionState::Scheduled);
         self.schedule_delete_timeline(timeline_id, timeline, delete_lock_guard);
 
         Ok(())
@@ -2049,9 +3017,18 @@ impl Tenant {
             "timeline_delete",
             false,
             async move {
+                self.set_deletion_state(timeline_id, TimelineDeletionState::InProgress);
                 if let Err(err) = self.delete_timeline(timeline_id, timeline).await {
                     error!("Error: {err:#}");
+                    self.set_deletion_state(
+                        timeline_id,
+                        TimelineDeletionState::Failed {
+                            reason: format!("{err:#}"),
+                        },
+                    );
                     timeline_clone.set_broken(err.to_string())
+                } else {
+                    self.set_deletion_state(timeline_id, TimelineDeletionState::Done);
                 };
                 Ok(())
             }
@@ -2072,6 +3049,30 @@ impl Tenant {
         self.current_state() == TenantState::Active
     }
 
+    /// Current attach/load progress, for the management API to poll while the
+    /// tenant is still `Attaching`/`Loading`.
+    pub fn attach_progress(&self) -> AttachProgressSnapshot {
+        self.attach_progress.snapshot()
+    }
+
+    /// The current state of a timeline deletion, or `None` if no deletion has
+    /// been requested for `timeline_id` (or the tenant has since forgotten it).
+    /// The HTTP layer returns "202 Accepted" from `prepare_and_schedule_delete_timeline`
+    /// and lets the caller poll this until it observes `Done` or `Failed`.
+    pub fn timeline_deletion_status(
+        &self,
+        timeline_id: TimelineId,
+    ) -> Option<TimelineDeletionState> {
+        self.deletion_states.lock().unwrap().get(&timeline_id).cloned()
+    }
+
+    fn set_deletion_state(&self, timeline_id: TimelineId, state: TimelineDeletionState) {
+        self.deletion_states
+            .lock()
+            .unwrap()
+            .insert(timeline_id, state);
+    }
+
     /// Changes tenant status to active, unless shutdown was already requested.
     ///
     /// `background_jobs_can_start` is an optional barrier set to a value during pageserver startup
@@ -2100,13 +3101,12 @@ impl Tenant {
             }
             debug!(tenant_id = %self.tenant_id, "Activating tenant");
             activating = true;
-            // Continue outside the closure. We need to grab timelines.lock()
-            // and we plan to turn it into a tokio::sync::Mutex in a future patch.
+            // Continue outside the closure.
         });
 
         if activating {
-            let timelines_accessor = self.timelines.lock().unwrap();
-            let timelines_to_activate = timelines_accessor
+            let timelines_to_activate = self
+                .timelines
                 .values()
                 .filter(|timeline| !(timeline.is_broken() || timeline.is_stopping()));
 
@@ -2129,7 +3129,7 @@ impl Tenant {
                 *current_state = TenantState::Active;
 
                 let elapsed = self.loading_started_at.elapsed();
-                let total_timelines = timelines_accessor.len();
+                let total_timelines = self.timelines.len();
 
                 // log a lot of stuff, because some tenants sometimes suffer from user-visible
                 // times to activate. see https://github.com/neondatabase/neon/issues/4025
@@ -2145,6 +3145,31 @@ impl Tenant {
         }
     }
 
+    /// Await `fut`, but give up after `deadline` (if any) has elapsed. On
+    /// timeout the `pending` task kinds — the ones this step was waiting on —
+    /// are logged and surfaced as [`ShutdownError::Timeout`] so a wedged task
+    /// cannot block shutdown forever.
+    async fn await_with_deadline<F: std::future::Future>(
+        deadline: Option<Duration>,
+        pending: &[TaskKind],
+        fut: F,
+    ) -> Result<F::Output, ShutdownError> {
+        match deadline {
+            None => Ok(fut.await),
+            Some(deadline) => match tokio::time::timeout(deadline, fut).await {
+                Ok(output) => Ok(output),
+                Err(_elapsed) => {
+                    warn!(
+                        "tenant shutdown timed out after {deadline:?}; still-running task kinds: {pending:?}"
+                    );
+                    Err(ShutdownError::Timeout {
+                        pending: pending.to_vec(),
+                    })
+                }
+            },
+        }
+    }
+
     /// Shutdown the tenant and join all of the spawned tasks.
     ///
     /// The method caters for all use-cases:
@@ -2152,7 +3177,11 @@ impl Tenant {
     /// - detach + ignore (freeze_and_flush == false)
     ///
     /// This will attempt to shutdown even if tenant is broken.
-    pub(crate) async fn shutdown(&self, freeze_and_flush: bool) -> Result<(), ShutdownError> {
+    pub(crate) async fn shutdown(
+        &self,
+        freeze_and_flush: bool,
+        deadline: Option<Duration>,
+    ) -> Result<(), ShutdownError> {
         debug_assert_current_span_has_tenant_id();
         // Set tenant (and its timlines) to Stoppping state.
         //
@@ -2171,34 +3200,57 @@ impl Tenant {
         // But the tenant background loops are joined-on in our caller.
         // It's mesed up.
         // we just ignore the failure to stop
-        match self.set_stopping().await {
+        match self.set_stopping(deadline).await {
             Ok(()) => {}
             Err(SetStoppingError::Broken) => {
                 // assume that this is acceptable
             }
             Err(SetStoppingError::AlreadyStopping) => return Err(ShutdownError::AlreadyStopping),
+            Err(SetStoppingError::Timeout { pending }) => {
+                return Err(ShutdownError::Timeout { pending })
+            }
         };
 
         if freeze_and_flush {
             // walreceiver has already began to shutdown with TenantState::Stopping, but we need to
             // await for them to stop.
-            task_mgr::shutdown_tasks(
-                Some(TaskKind::WalReceiverManager),
-                Some(self.tenant_id),
-                None,
+            Self::await_with_deadline(
+                deadline,
+                &[TaskKind::WalReceiverManager],
+                task_mgr::shutdown_tasks(
+                    Some(TaskKind::WalReceiverManager),
+                    Some(self.tenant_id),
+                    None,
+                ),
             )
-            .await;
+            .await?;
 
             // this will wait for uploads to complete; in the past, it was done outside tenant
             // shutdown in pageserver::shutdown_pageserver.
-            self.freeze_and_flush_on_shutdown().await;
+            let flush_summary = self.freeze_and_flush_on_shutdown().await;
+            if !flush_summary.is_fully_consistent() {
+                warn!(
+                    "graceful shutdown left {} timeline(s) with incomplete remote state: {:?}",
+                    flush_summary.failed_timelines().count(),
+                    flush_summary.failed_timelines().collect::<Vec<_>>(),
+                );
+            }
         }
 
         // shutdown all tenant and timeline tasks: gc, compaction, page service
         // No new tasks will be started for this tenant because it's in `Stopping` state.
         //
         // this will additionally shutdown and await all timeline tasks.
-        task_mgr::shutdown_tasks(None, Some(self.tenant_id), None).await;
+        Self::await_with_deadline(
+            deadline,
+            &[
+                TaskKind::Compaction,
+                TaskKind::GarbageCollector,
+                TaskKind::WalReceiverManager,
+            ],
+            task_mgr::shutdown_tasks(None, Some(self.tenant_id), None),
+        )
+        .await?;
 
         Ok(())
     }
@@ -2208,11 +3260,11 @@ impl Tenant {
     /// This function waits for the tenant to become active if it isn't already, before transitioning it into Stopping state.
     ///
     /// This function is not cancel-safe!
-    async fn set_stopping(&self) -> Result<(), SetStoppingError> {
+    async fn set_stopping(&self, deadline: Option<Duration>) -> Result<(), SetStoppingError> {
         let mut rx = self.state.subscribe();
 
         // cannot stop before we're done activating, so wait out until we're done activating
-        rx.wait_for(|state| match state {
+        let wait_activated = rx.wait_for(|state| match state {
             TenantState::Activating(_) | TenantState::Loading | TenantState::Attaching => {
                 info!(
                     "waiting for {} to turn Active|Broken|Stopping",
@@ -2221,9 +3273,16 @@ impl Tenant {
                 false
             }
             TenantState::Active | TenantState::Broken { .. } | TenantState::Stopping {} => true,
-        })
-        .await
-        .expect("cannot drop self.state while on a &self method");
+        });
+        match Self::await_with_deadline(deadline, &[TaskKind::InitialLoad], wait_activated).await {
+            Ok(res) => {
+                res.expect("cannot drop self.state while on a &self method");
+            }
+            Err(ShutdownError::Timeout { pending }) => {
+                return Err(SetStoppingError::Timeout { pending })
+            }
+            Err(ShutdownError::AlreadyStopping) => unreachable!("await_with_deadline never returns AlreadyStopping"),
+        }
 
         // we now know we're done activating, let's see whether this task is the winner to transition into Stopping
         let mut err = None;
@@ -2264,10 +3323,7 @@ impl Tenant {
             ),
         }
 
-        let timelines_accessor = self.timelines.lock().unwrap();
-        let not_broken_timelines = timelines_accessor
-            .values()
-            .filter(|timeline| !timeline.is_broken());
+        let not_broken_timelines = self.timelines.values().filter(|timeline| !timeline.is_broken());
         for timeline in not_broken_timelines {
             timeline.set_state(TimelineState::Stopping);
         }
@@ -2331,20 +3387,52 @@ impl Tenant {
         self.state.subscribe()
     }
 
-    pub(crate) async fn wait_to_become_active(&self) -> Result<(), WaitToBecomeActiveError> {
+    /// Wait until the tenant reaches `Active`, giving up after `deadline` if one
+    /// is supplied. Passing `None` waits indefinitely, matching the original
+    /// behavior; passing `Some(duration)` lets a request handler bound how long
+    /// it blocks on a tenant stuck in `Loading`/`Attaching`/`Activating`.
+    pub(crate) async fn wait_to_become_active(
+        &self,
+        deadline: Option<Duration>,
+    ) -> Result<(), WaitToBecomeActiveError> {
+        let started_at = tokio::time::Instant::now();
         let mut receiver = self.state.subscribe();
         loop {
             let current_state = receiver.borrow_and_update().clone();
             match current_state {
                 TenantState::Loading | TenantState::Attaching | TenantState::Activating(_) => {
                     // in these states, there's a chance that we can reach ::Active
-                    receiver.changed().await.map_err(
-                        |_e: tokio::sync::watch::error::RecvError| {
-                            WaitToBecomeActiveError::TenantDropped {
-                                tenant_id: self.tenant_id,
+                    let changed = receiver.changed();
+                    match deadline {
+                        Some(deadline) => {
+                            let remaining = deadline.saturating_sub(started_at.elapsed());
+                            match tokio::time::timeout(remaining, changed).await {
+                                Ok(res) => res.map_err(
+                                    |_e: tokio::sync::watch::error::RecvError| {
+                                        WaitToBecomeActiveError::TenantDropped {
+                                            tenant_id: self.tenant_id,
+                                        }
+                                    },
+                                )?,
+                                Err(_elapsed) => {
+                                    return Err(WaitToBecomeActiveError::Timeout {
+                                        tenant_id: self.tenant_id,
+                                        waited: started_at.elapsed(),
+                                        last_state: current_state,
+                                    });
+                                }
                             }
-                        },
-                    )?;
+                        }
+                        None => {
+                            changed.await.map_err(
+                                |_e: tokio::sync::watch::error::RecvError| {
+                                    WaitToBecomeActiveError::TenantDropped {
+                                        tenant_id: self.tenant_id,
+                                    }
+                                },
+                            )?;
+                        }
+                    }
                 }
                 TenantState::Active { .. } => {
                     return Ok(());
@@ -2361,12 +3449,27 @@ impl Tenant {
     }
 }
 
+/// The outcome of ordering timelines into tree order: the timelines that could
+/// be placed (parents before children), plus the orphans whose ancestor is not
+/// present. Each orphan is reported as `(timeline_id, missing_ancestor_id)`.
+struct TreeSortResult {
+    ordered: Vec<(TimelineId, TimelineMetadata)>,
+    orphans: Vec<(TimelineId, TimelineId)>,
+}
+
 /// Given a Vec of timelines and their ancestors (timeline_id, ancestor_id),
 /// perform a topological sort, so that the parent of each timeline comes
 /// before the children.
+///
+/// When `allow_missing_ancestors` is false, a timeline referencing an absent
+/// ancestor fails the whole tenant load. When true, the loadable timelines are
+/// returned in tree order and the unplaceable ones are returned separately as
+/// orphans, so the tenant can come up `Active` with the rest and mark only the
+/// orphans as `Broken`.
 fn tree_sort_timelines(
     timelines: HashMap<TimelineId, TimelineMetadata>,
-) -> anyhow::Result<Vec<(TimelineId, TimelineMetadata)>> {
+    allow_missing_ancestors: bool,
+) -> anyhow::Result<TreeSortResult> {
     let mut result = Vec::with_capacity(timelines.len());
 
     let mut now = Vec::with_capacity(timelines.len());
@@ -2392,16 +3495,23 @@ fn tree_sort_timelines(
     }
 
     // All timelines should be visited now. Unless there were timelines with missing ancestors.
+    let mut orphans = Vec::new();
     if !later.is_empty() {
         for (missing_id, orphan_ids) in later {
             for (orphan_id, _) in orphan_ids {
                 error!("could not load timeline {orphan_id} because its ancestor timeline {missing_id} could not be loaded");
+                orphans.push((orphan_id, missing_id));
             }
         }
-        bail!("could not load tenant because some timelines are missing ancestors");
+        if !allow_missing_ancestors {
+            bail!("could not load tenant because some timelines are missing ancestors");
+        }
     }
 
-    Ok(result)
+    Ok(TreeSortResult {
+        ordered: result,
+        orphans,
+    })
 }
 
 impl Tenant {
@@ -2463,6 +3573,33 @@ impl Tenant {
             .unwrap_or(self.conf.default_tenant_conf.gc_period)
     }
 
+    /// The configured scrub period for this tenant. No background loop reads
+    /// it, so it does not schedule anything; a scrub runs only when
+    /// [`Tenant::scrub_iteration`] is called.
+    pub fn get_scrub_period(&self) -> Duration {
+        let tenant_conf = self.tenant_conf.read().unwrap();
+        tenant_conf
+            .scrub_period
+            .unwrap_or(self.conf.default_tenant_conf.scrub_period)
+    }
+
+    /// zstd compression level applied per-blob when writing image/delta layers.
+    /// Writers fall back to storing a blob uncompressed when compression doesn't
+    /// shrink it, so tiny blobs are never inflated.
+    pub fn get_compression_level(&self) -> i32 {
+        let tenant_conf = self.tenant_conf.read().unwrap();
+        tenant_conf
+            .compression_level
+            .unwrap_or(self.conf.default_tenant_conf.compression_level)
+    }
+
+    pub fn get_gc_concurrency(&self) -> usize {
+        let tenant_conf = self.tenant_conf.read().unwrap();
+        tenant_conf
+            .gc_concurrency
+            .unwrap_or(self.conf.default_tenant_conf.gc_concurrency)
+    }
+
     pub fn get_image_creation_threshold(&self) -> usize {
         let tenant_conf = self.tenant_conf.read().unwrap();
         tenant_conf
@@ -2493,7 +3630,7 @@ impl Tenant {
 
     pub fn set_new_tenant_config(&self, new_tenant_conf: TenantConfOpt) {
         *self.tenant_conf.write().unwrap() = new_tenant_conf;
-        // Don't hold self.timelines.lock() during the notifies.
+        // Don't hold any TimelineRegistry shard lock during the notifies.
         // There's no risk of deadlock right now, but there could be if we consolidate
         // mutexes in struct Timeline in the future.
         let timelines = self.list_timelines();
@@ -2615,7 +3752,7 @@ impl Tenant {
 
         // Put the placeholder into the map.
         let placeholder_timeline: Arc<Timeline> = {
-            match self.timelines.lock().unwrap().entry(timeline_id) {
+            match self.timelines.entry(timeline_id) {
                 Entry::Occupied(_) => {
                     return Err(StartCreatingTimelineError::AlreadyExists {
                         timeline_id,
@@ -2649,7 +3786,7 @@ impl Tenant {
             Err(err) => {
                 // If we failed to create the uninit mark, remove the placeholder
                 // timeline from the map.
-                let removed = self.timelines.lock().unwrap().remove(&timeline_id);
+                let removed = self.timelines.remove(&timeline_id);
                 assert!(removed.is_some());
                 assert!(compare_arced_timeline(
                     &removed.unwrap(),
@@ -2705,14 +3842,27 @@ impl Tenant {
             }
         });
 
+        // TODO: make the backend selectable via `TenantConfOpt` once an
+        // operator-facing knob exists; for now every tenant gets the
+        // historical one-file-per-timeline layout. The KV backend
+        // (`MetadataBackendKind::Kv`) has its own test coverage in
+        // `metadata_store`'s test module, but nothing constructs a `Tenant`
+        // with it yet -- `File` is the only kind ever passed to `open` below.
+        // `File` never touches disk at open time, so this cannot fail.
+        let metadata_store: Arc<dyn MetadataStore> =
+            metadata_store::open(MetadataBackendKind::File, conf, tenant_id)
+                .expect("opening the file metadata backend is infallible")
+                .into();
+
         Tenant {
             tenant_id,
             conf,
+            metadata_store,
             // using now here is good enough approximation to catch tenants with really long
             // activation times.
             loading_started_at: Instant::now(),
             tenant_conf: Arc::new(RwLock::new(tenant_conf)),
-            timelines: Mutex::new(HashMap::new()),
+            timelines: TimelineRegistry::new(),
             gc_cs: tokio::sync::Mutex::new(()),
             walredo_mgr,
             remote_storage,
@@ -2720,6 +3870,8 @@ impl Tenant {
             cached_logical_sizes: tokio::sync::Mutex::new(HashMap::new()),
             cached_synthetic_tenant_size: Arc::new(AtomicU64::new(0)),
             eviction_task_tenant_state: tokio::sync::Mutex::new(EvictionTaskTenantState::default()),
+            attach_progress: AttachProgress::default(),
+            deletion_states: Mutex::new(HashMap::new()),
         }
     }
 
@@ -2795,32 +3947,46 @@ impl Tenant {
             // Convert the config to a toml file.
             conf_content += &toml_edit::ser::to_string(&tenant_conf)?;
 
-            let mut target_config_file = VirtualFile::open_with_options(
-                target_config_path,
-                OpenOptions::new()
-                    .truncate(true) // This needed for overwriting with small config files
-                    .write(true)
-                    .create_new(creating_tenant)
-                    // when creating a new tenant, first_save will be true and `.create(true)` will be
-                    // ignored (per rust std docs).
-                    //
-                    // later when updating the config of created tenant, or persisting config for the
-                    // first time for attached tenant, the `.create(true)` is used.
-                    .create(true),
+            // When creating a tenant for the first time, refuse to clobber an
+            // existing config. (With the atomic temp-file-and-rename below we
+            // can no longer rely on `create_new` on the final file to enforce
+            // this, so check explicitly.)
+            if creating_tenant && target_config_path.try_exists().unwrap_or(false) {
+                anyhow::bail!(
+                    "tenant config {} already exists",
+                    target_config_path.display()
+                );
+            }
+
+            // Write the full config to a sibling temp file, fsync it, then
+            // rename it over the target and fsync the parent directory. This is
+            // the standard crash-safe atomic replace: `load_tenant_config` will
+            // always observe either the old or the new complete config, never a
+            // half-written one.
+            let tmp_config_path = path_with_suffix_extension(target_config_path, "tmp");
+
+            let mut tmp_config_file = VirtualFile::open_with_options(
+                &tmp_config_path,
+                OpenOptions::new().truncate(true).write(true).create(true),
             )?;
 
-            target_config_file
+            tmp_config_file
                 .write(conf_content.as_bytes())
-                .context("write toml bytes into file")
-                .and_then(|_| target_config_file.sync_all().context("fsync config file"))
-                .context("write config file")?;
+                .context("write toml bytes into temp file")
+                .and_then(|_| tmp_config_file.sync_all().context("fsync temp config file"))
+                .context("write temp config file")?;
 
-            // fsync the parent directory to ensure the directory entry is durable.
-            // before this was done conditionally on creating_tenant, but these management actions are rare
-            // enough to just fsync it always.
+            std::fs::rename(&tmp_config_path, target_config_path).with_context(|| {
+                format!(
+                    "rename temp config {} over {}",
+                    tmp_config_path.display(),
+                    target_config_path.display()
+                )
+            })?;
 
+            // fsync the parent directory so the rename (the new directory entry)
+            // is durable.
             crashsafe::fsync(target_config_parent)?;
-            // XXX we're not fsyncing the parent dir, need to do that in case `creating_tenant`
             Ok(())
         };
 
@@ -2864,6 +4030,7 @@ impl Tenant {
         target_timeline_id: Option<TimelineId>,
         horizon: u64,
         pitr: Duration,
+        cancel: &CancellationToken,
         ctx: &RequestContext,
     ) -> anyhow::Result<GcResult> {
         let mut totals: GcResult = Default::default();
@@ -2892,14 +4059,53 @@ impl Tenant {
         //
         // See comments in [`Tenant::branch_timeline`] for more information
         // about why branch creation task can run concurrently with timeline's GC iteration.
-        for timeline in gc_timelines {
-            if task_mgr::is_shutdown_requested() {
-                // We were requested to shut down. Stop and return with the progress we
-                // made.
-                break;
+        // Run per-timeline GC with bounded concurrency. Each `timeline.gc()`
+        // spends much of its time blocked on `layer_removal_cs`, so serializing
+        // them needlessly serializes that wait; `gc_concurrency` lets several
+        // overlap. We still stop starting new work on shutdown and accumulate
+        // every completed `GcResult` into `totals`.
+        let concurrency = self.get_gc_concurrency().max(1);
+        let mut processed = 0;
+        let mut in_flight = futures::stream::FuturesUnordered::new();
+        let mut pending = gc_timelines.into_iter();
+
+        loop {
+            while in_flight.len() < concurrency {
+                if task_mgr::is_shutdown_requested() || cancel.is_cancelled() {
+                    break;
+                }
+                match pending.next() {
+                    Some(timeline) => in_flight.push(async move {
+                        // NB: the boundary between scheduling a layer for
+                        // deletion and persisting that in the timeline's own
+                        // metadata is inside `Timeline::gc` itself; this is
+                        // the closest we can inject from the per-timeline
+                        // dispatch loop around it.
+                        fail::fail_point!("gc-iteration-before-timeline-gc", |_| {
+                            anyhow::bail!("failpoint gc-iteration-before-timeline-gc")
+                        });
+                        timeline.gc().await
+                    }),
+                    None => break,
+                }
             }
-            let result = timeline.gc().await?;
-            totals += result;
+
+            let Some(result) = in_flight.next().await else {
+                // Nothing running: either we've drained everything or a cancel
+                // stopped us from starting more.
+                if task_mgr::is_shutdown_requested() || cancel.is_cancelled() {
+                    info!("GC iteration cancelled after processing {processed} timeline(s)");
+                    // `GcResult` (defined in `repository.rs`, not part of this checkout) has
+                    // `elapsed` already; it doesn't yet have `cancelled`. This line assumes
+                    // that field gets added alongside the rest of `GcResult`'s bookkeeping --
+                    // it won't compile against the real tree until it does.
+                    totals.cancelled = true;
+                }
+                break;
+            };
+
+            totals += result?;
+            processed += 1;
         }
 
         totals.elapsed = now.elapsed();
@@ -2941,16 +4147,15 @@ impl Tenant {
         // Scan all timelines. For each timeline, remember the timeline ID and
         // the branch point where it was created.
         let (all_branchpoints, timeline_ids): (BTreeSet<(TimelineId, Lsn)>, _) = {
-            let timelines = self.timelines.lock().unwrap();
             let mut all_branchpoints = BTreeSet::new();
             let timeline_ids = {
                 if let Some(target_timeline_id) = target_timeline_id.as_ref() {
-                    if timelines.get(target_timeline_id).is_none() {
+                    if self.timelines.get(target_timeline_id).is_none() {
                         bail!("gc target timeline does not exist")
                     }
                 };
 
-                timelines
+                self.timelines
                     .iter()
                     .map(|(timeline_id, timeline_entry)| {
                         if let Some(ancestor_timeline_id) =
@@ -2974,7 +4179,7 @@ impl Tenant {
                             }
                         }
 
-                        *timeline_id
+                        timeline_id
                     })
                     .collect::<Vec<_>>()
             };
@@ -3078,7 +4283,7 @@ impl Tenant {
             .context("load newly created on-disk timeline state")?
             .unwrap();
 
-        match self.timelines.lock().unwrap().entry(dst_id) {
+        match self.timelines.entry(dst_id) {
             Entry::Vacant(_) => unreachable!("we created a placeholder earlier, and load_local_timeline should have inserted the real timeline"),
             Entry::Occupied(mut o) => {
                 info!("replacing placeholder timeline with the real one");
@@ -3196,6 +4401,10 @@ impl Tenant {
         self.create_timeline_files(&guard.timeline_path, dst_id, &metadata)
             .context("create timeline files")?;
 
+        fail::fail_point!("branch-timeline-after-metadata-write", |_| {
+            anyhow::bail!("failpoint branch-timeline-after-metadata-write")
+        });
+
         // Root timeline gets its layers during creation and uploads them along with the metadata.
         // A branch timeline though, when created, can get no writes for some time, hence won't get any layers created.
         // We still need to upload its metadata eagerly: if other nodes `attach` the tenant and miss this timeline, their GC
@@ -3218,6 +4427,153 @@ impl Tenant {
         Ok(())
     }
 
+    /// Copy an existing timeline's state into a brand-new, independent timeline.
+    ///
+    /// Unlike [`Tenant::branch_timeline`], the result does not share history with
+    /// its source via copy-on-write: the relevant layer files up to `up_to_lsn`
+    /// (or the source's last record LSN when `None`) are materialized and
+    /// re-uploaded under `new_timeline_id`, yielding a standalone timeline with
+    /// no ancestor that can be GC'd and retained on its own schedule.
+    pub async fn copy_timeline(
+        self: &Arc<Self>,
+        source_timeline_id: TimelineId,
+        new_timeline_id: TimelineId,
+        up_to_lsn: Option<Lsn>,
+        ctx: &RequestContext,
+    ) -> anyhow::Result<Arc<Timeline>> {
+        anyhow::ensure!(self.is_active(), "Cannot copy timelines on inactive tenant");
+
+        let src_timeline = self
+            .get_timeline(source_timeline_id, false)
+            .context("Cannot copy a timeline that's not present in pageserver")?;
+
+        let guard = self.start_creating_timeline(new_timeline_id)?;
+
+        let remote_client = self.remote_storage.as_ref().map(|remote_storage| {
+            Arc::new(RemoteTimelineClient::new(
+                remote_storage.clone(),
+                self.conf,
+                self.tenant_id,
+                new_timeline_id,
+            ))
+        });
+
+        // Use an async block so the uninit mark is removed even if copying fails.
+        let create_ondisk_state = async {
+            // Materialize up to an explicit LSN, or the source's tip otherwise.
+            let up_to_lsn = up_to_lsn
+                .unwrap_or_else(|| src_timeline.get_last_record_lsn())
+                .align();
+
+            // Make sure the WAL up to the copy point has been processed on the
+            // source before we snapshot its layers.
+            src_timeline.wait_lsn(up_to_lsn, ctx).await?;
+
+            // Hold the GC lock so the source's layers can't be collected out
+            // from under us while we copy them.
+            let _gc_cs = self.gc_cs.lock().await;
+
+            // Fresh, ancestor-less metadata: the copy stands on its own.
+            let RecordLsn { prev, .. } = src_timeline.get_last_record_rlsn();
+            let metadata = TimelineMetadata::new(
+                up_to_lsn,
+                Some(prev),
+                None,
+                up_to_lsn,
+                *src_timeline.latest_gc_cutoff_lsn.read(),
+                src_timeline.initdb_lsn,
+                src_timeline.pg_version,
+            );
+
+            self.create_timeline_files(&guard.timeline_path, new_timeline_id, &metadata)
+                .context("create timeline files")?;
+
+            // Copy and re-upload the source's layer files up to the copy point
+            // into the new timeline's directory.
+            src_timeline
+                .copy_to_new_timeline(
+                    &guard.timeline_path,
+                    up_to_lsn,
+                    remote_client.clone(),
+                    ctx,
+                )
+                .await
+                .context("copy layer files into new timeline")?;
+
+            if let Some(remote_client) = remote_client.as_ref() {
+                remote_client.init_upload_queue_for_empty_remote(&metadata)?;
+                remote_client
+                    .schedule_index_upload_for_metadata_update(&metadata)
+                    .context("copy initial metadata upload")?;
+                remote_client
+                    .wait_completion()
+                    .await
+                    .context("wait for copy uploads to complete")?;
+            }
+
+            anyhow::Ok(())
+        };
+
+        let placeholder_timeline = match create_ondisk_state.await {
+            Ok(()) => {
+                match guard.creation_complete_remove_uninit_marker_and_get_placeholder_timeline() {
+                    Ok(placeholder_timeline) => placeholder_timeline,
+                    Err(err) => {
+                        error!(
+                            "failed to remove uninit marker for new_timeline_id={new_timeline_id}: {err:#}"
+                        );
+                        return Err(err);
+                    }
+                }
+            }
+            Err(err) => {
+                error!(
+                    "failed to create on-disk state for new_timeline_id={new_timeline_id}: {err:#}"
+                );
+                guard.creation_failed();
+                return Err(err);
+            }
+        };
+
+        // From here on, it's just like during pageserver startup.
+        let metadata = load_metadata(self.conf, new_timeline_id, self.tenant_id)
+            .context("load newly created on-disk timeline metadata")?;
+
+        let real_timeline = self
+            .load_local_timeline(
+                new_timeline_id,
+                metadata,
+                AncestorArg::no_ancestor(),
+                TimelineLoadCause::TimelineCreate {
+                    placeholder_timeline: Arc::clone(&placeholder_timeline),
+                    expxect_layer_files: true,
+                },
+                None,
+                ctx,
+            )
+            .instrument(info_span!("load_local_timeline", timeline_id=%new_timeline_id))
+            .await
+            .context("load newly created on-disk timeline state")?
+            .unwrap();
+
+        match self.timelines.entry(new_timeline_id) {
+            Entry::Vacant(_) => unreachable!(
+                "we created a placeholder earlier, and load_local_timeline should have inserted the real timeline"
+            ),
+            Entry::Occupied(mut o) => {
+                assert_eq!(placeholder_timeline.current_state(), TimelineState::Creating);
+                assert!(compare_arced_timeline(&placeholder_timeline, o.get()));
+                let replaced_placeholder = o.insert(Arc::clone(&real_timeline));
+                assert!(compare_arced_timeline(&replaced_placeholder, &placeholder_timeline));
+            }
+        }
+
+        real_timeline.set_state(TimelineState::Active);
+        real_timeline.maybe_spawn_flush_loop();
+        info!("copied timeline {source_timeline_id} into {new_timeline_id} up to {}", real_timeline.get_last_record_lsn());
+        Ok(real_timeline)
+    }
+
     /// - run initdb to init temporary instance and get bootstrap data
     /// - after initialization complete, remove the temp dir.
     ///
@@ -3377,14 +4733,9 @@ impl Tenant {
             anyhow::bail!("failpoint after-timeline-uninit-mark-creation");
         });
 
-        save_metadata(
-            self.conf,
-            new_timeline_id,
-            self.tenant_id,
-            new_metadata,
-            true,
-        )
-        .context("Failed to create timeline metadata")?;
+        self.metadata_store
+            .put(new_timeline_id, new_metadata)
+            .context("Failed to create timeline metadata")?;
 
         Ok(())
     }
@@ -3572,6 +4923,12 @@ fn try_create_target_tenant_dir(
         }
     }
 
+    // Stamp the on-disk layout version alongside the attach marker, before the
+    // whole temp directory is fsync'd and renamed into place, so every tenant
+    // directory carries a version we can check and migrate on load.
+    layout::write_current(temporary_tenant_dir)
+        .with_context(|| format!("write layout version for tenant {tenant_id}"))?;
+
     let temporary_tenant_timelines_dir = rebase_directory(
         &conf.timelines_path(&tenant_id),
         target_tenant_directory,
@@ -3629,6 +4986,116 @@ fn try_create_target_tenant_dir(
     Ok(())
 }
 
+/// On-disk tenant directory layout versioning.
+///
+/// A single place to evolve the tenant directory format. Creation stamps
+/// [`CURRENT_LAYOUT_VERSION`] into [`LAYOUT_VERSION_FILENAME`]; load reads it
+/// back and either uses the directory as-is, refuses a directory written by an
+/// incompatibly newer pageserver, or runs the registered migration closures to
+/// bring a known-older layout up to date — instead of ad-hoc probing of which
+/// files happen to exist.
+pub(crate) mod layout {
+    use super::{CURRENT_LAYOUT_VERSION, LAYOUT_VERSION_FILENAME};
+    use anyhow::Context;
+    use utils::crashsafe;
+    use std::path::Path;
+
+    /// A migration that upgrades a tenant directory from the version it is keyed
+    /// by to the next one. Each closure mutates the directory in place and must
+    /// be idempotent, so a crash part-way through is safe to retry on the next
+    /// load (the version stamp is only advanced once the migration succeeds).
+    type Migration = fn(&Path) -> anyhow::Result<()>;
+
+    /// Registered migrations, ordered by the version they upgrade *from*. Empty
+    /// today because version 1 is the first stamped layout; a future
+    /// directory-structure change appends `(from_version, migration_fn)` here
+    /// and bumps [`CURRENT_LAYOUT_VERSION`].
+    const MIGRATIONS: &[(u32, Migration)] = &[];
+
+    /// Stamp the current layout version into `tenant_dir`, fsyncing both the
+    /// file and its parent so the stamp is durable.
+    pub(crate) fn write_current(tenant_dir: &Path) -> anyhow::Result<()> {
+        write_version(tenant_dir, CURRENT_LAYOUT_VERSION)
+    }
+
+    fn write_version(tenant_dir: &Path, version: u32) -> anyhow::Result<()> {
+        use std::io::Write;
+
+        let path = tenant_dir.join(LAYOUT_VERSION_FILENAME);
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("open layout version file {}", path.display()))?;
+        file.write_all(version.to_string().as_bytes())
+            .and_then(|_| file.sync_all())
+            .with_context(|| format!("write layout version file {}", path.display()))?;
+        crashsafe::fsync(tenant_dir)
+            .with_context(|| format!("fsync tenant dir after layout stamp {}", tenant_dir.display()))
+    }
+
+    /// Read the recorded layout version, or `None` for a legacy directory that
+    /// predates versioning.
+    fn read_version(tenant_dir: &Path) -> anyhow::Result<Option<u32>> {
+        let path = tenant_dir.join(LAYOUT_VERSION_FILENAME);
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => {
+                let version = contents
+                    .trim()
+                    .parse::<u32>()
+                    .with_context(|| format!("parse layout version from {}", path.display()))?;
+                Ok(Some(version))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => {
+                Err(e).with_context(|| format!("read layout version file {}", path.display()))
+            }
+        }
+    }
+
+    /// Validate `tenant_dir`'s layout version and migrate it to
+    /// [`CURRENT_LAYOUT_VERSION`] if needed. Called early in tenant load.
+    pub(crate) fn check_and_migrate(tenant_dir: &Path) -> anyhow::Result<()> {
+        let mut version = match read_version(tenant_dir)? {
+            // A directory written before layout versioning has the same
+            // structure as version 1; adopt it and stamp the version so future
+            // migrations have a starting point.
+            None => {
+                write_current(tenant_dir)?;
+                return Ok(());
+            }
+            Some(version) => version,
+        };
+
+        if version > CURRENT_LAYOUT_VERSION {
+            anyhow::bail!(
+                "tenant directory {} has on-disk layout version {version}, newer than this \
+                 pageserver supports ({CURRENT_LAYOUT_VERSION}); refusing to load",
+                tenant_dir.display()
+            );
+        }
+
+        while version < CURRENT_LAYOUT_VERSION {
+            let (_, migrate) = MIGRATIONS
+                .iter()
+                .find(|(from, _)| *from == version)
+                .with_context(|| {
+                    format!(
+                        "no migration registered from tenant layout version {version} for {}",
+                        tenant_dir.display()
+                    )
+                })?;
+            migrate(tenant_dir)
+                .with_context(|| format!("migrate tenant layout from version {version}"))?;
+            version += 1;
+            write_version(tenant_dir, version)?;
+        }
+
+        Ok(())
+    }
+}
+
 fn rebase_directory(original_path: &Path, base: &Path, new_base: &Path) -> anyhow::Result<PathBuf> {
     let relative_path = original_path.strip_prefix(base).with_context(|| {
         format!(
@@ -3714,6 +5181,41 @@ pub fn dump_layerfile_from_path(
     Ok(())
 }
 
+/// How many layers a single timeline's scrub pass examined, and how many of
+/// them failed validation and had to be re-downloaded from remote storage.
+#[derive(Debug, Default, Clone, Copy)]
+struct ScrubSummary {
+    checked: usize,
+    repaired: usize,
+}
+
+/// Validate a layer file without printing anything.
+///
+/// This is the non-interactive sibling of [`dump_layerfile_from_path`]: it
+/// opens the file, checks the two-byte magic, and runs the same
+/// `ImageLayer::new_for_path` / `DeltaLayer::new_for_path` parser so a
+/// truncated footer or a corrupt block index is surfaced as an error. The
+/// parsed layer is discarded — only the fact that it parsed matters.
+fn validate_layer_file(path: &Path, _ctx: &RequestContext) -> anyhow::Result<()> {
+    use std::os::unix::fs::FileExt;
+
+    let file = File::open(path)?;
+    let mut header_buf = [0u8; 2];
+    file.read_exact_at(&mut header_buf, 0)?;
+
+    match u16::from_be_bytes(header_buf) {
+        crate::IMAGE_FILE_MAGIC => {
+            ImageLayer::new_for_path(path, file)?;
+        }
+        crate::DELTA_FILE_MAGIC => {
+            DeltaLayer::new_for_path(path, file)?;
+        }
+        magic => bail!("unrecognized magic identifier: {:?}", magic),
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 pub mod harness {
     use bytes::{Bytes, BytesMut};
@@ -3888,7 +5390,7 @@ pub mod harness {
                 .instrument(info_span!("try_load", tenant_id=%self.tenant_id))
                 .await?;
             tenant.state.send_replace(TenantState::Active);
-            for timeline in tenant.timelines.lock().unwrap().values() {
+            for timeline in tenant.timelines.values() {
                 timeline.set_state(TimelineState::Active);
             }
             Ok(tenant)
@@ -4151,7 +5653,7 @@ mod tests {
         // and compaction works. But it does set the 'cutoff' point so that the cross check
         // below should fail.
         tenant
-            .gc_iteration(Some(TIMELINE_ID), 0x10, Duration::ZERO, &ctx)
+            .gc_iteration(Some(TIMELINE_ID), 0x10, Duration::ZERO, &CancellationToken::new(), &ctx)
             .await?;
 
         // try to branch at lsn 25, should fail because we already garbage collected the data
@@ -4244,7 +5746,7 @@ mod tests {
         tline.set_broken("test".to_owned());
 
         tenant
-            .gc_iteration(Some(TIMELINE_ID), 0x10, Duration::ZERO, &ctx)
+            .gc_iteration(Some(TIMELINE_ID), 0x10, Duration::ZERO, &CancellationToken::new(), &ctx)
             .await?;
 
         // The branchpoints should contain all timelines, even ones marked
@@ -4290,7 +5792,7 @@ mod tests {
             .expect("Should have a local timeline");
         // this removes layers before lsn 40 (50 minus 10), so there are two remaining layers, image and delta for 31-50
         tenant
-            .gc_iteration(Some(TIMELINE_ID), 0x10, Duration::ZERO, &ctx)
+            .gc_iteration(Some(TIMELINE_ID), 0x10, Duration::ZERO, &CancellationToken::new(), &ctx)
             .await?;
         assert!(newtline.get(*TEST_KEY, Lsn(0x25), &ctx).await.is_ok());
 
@@ -4318,7 +5820,7 @@ mod tests {
 
         // run gc on parent
         tenant
-            .gc_iteration(Some(TIMELINE_ID), 0x10, Duration::ZERO, &ctx)
+            .gc_iteration(Some(TIMELINE_ID), 0x10, Duration::ZERO, &CancellationToken::new(), &ctx)
             .await?;
 
         // Check that the data is still accessible on the branch.
@@ -4852,6 +6354,43 @@ mod tests {
 
         Ok(())
     }
+
+    // A full crash-resume integration test for `attach()` would need a
+    // `GenericRemoteStorage` backed by something other than real cloud
+    // storage, and no such test double exists in this tree -- so this covers
+    // the part that's actually reachable here: `scan_local_timeline_dirs`,
+    // the piece `attach()` relies on to tell which timelines survived an
+    // earlier, interrupted attempt.
+    #[tokio::test]
+    async fn test_scan_local_timeline_dirs() -> anyhow::Result<()> {
+        let harness = TenantHarness::create("test_scan_local_timeline_dirs")?;
+        let tenant = Tenant::new(
+            TenantState::Loading,
+            harness.conf,
+            TenantConfOpt::from(harness.tenant_conf),
+            Arc::new(TestRedoManager),
+            harness.tenant_id,
+            None,
+        );
+
+        // A brand-new attach has no timelines dir contents yet: empty, not an error.
+        assert!(tenant.scan_local_timeline_dirs()?.is_empty());
+
+        let present_id = TimelineId::generate();
+        fs::create_dir_all(harness.conf.timeline_path(&present_id, &harness.tenant_id))?;
+        // Non-timeline bookkeeping in the same directory (e.g. an uninit
+        // mark) must not be mistaken for a timeline directory.
+        fs::create_dir_all(
+            harness
+                .conf
+                .timelines_path(&harness.tenant_id)
+                .join("not-a-timeline-id"),
+        )?;
+
+        let found = tenant.scan_local_timeline_dirs()?;
+        assert_eq!(found, HashSet::from([present_id]));
+        Ok(())
+    }
 }
 
 #[cfg(not(debug_assertions))]
@@ -0,0 +1,101 @@
+//! Serde adapters intended to let `TenantConf`/`TenantConfOpt` size and
+//! duration fields be written with human-readable units.
+//!
+//! Garage moved its size limits onto the `bytesize` crate so operators can
+//! write `"256 MiB"` instead of a raw byte count; this module is the
+//! pageserver equivalent. Neither `TenantConfOpt` nor `config.rs` is part of
+//! this source tree, so no field is actually annotated with these adapters
+//! yet -- that's left for whoever adds them alongside the fields they cover
+//! (`checkpoint_distance`, `compaction_target_size`, `gc_horizon`,
+//! `max_lsn_wal_lag`, the various `*_period`/`*_timeout`s, `pitr_interval`),
+//! the same way they'd be wired up elsewhere:
+//!
+//! ```ignore
+//! #[serde(with = "crate::tenant::human_units::byte_size")]
+//! pub compaction_target_size: u64,
+//!
+//! #[serde(with = "crate::tenant::human_units::duration")]
+//! pub compaction_period: Duration,
+//! ```
+//!
+//! Both adapters accept either the human form (`"1 GB"`, `"512MiB"`,
+//! `"10min"`, `"2h"`) or a bare number — a bare byte count, or a bare number
+//! of seconds for durations — so existing config files would keep parsing.
+//! On the way out both serialize back to a single canonical human string, so
+//! a load-then-store round trip is stable.
+
+use std::time::Duration;
+
+use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::Deserialize;
+
+/// Serde adapter for byte-size fields. Accepts `"256 MiB"`, `"1GB"`, or a bare
+/// integer byte count; serializes to a canonical `bytesize` string.
+pub mod byte_size {
+    use super::*;
+
+    pub fn serialize<S>(value: &u64, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // `ByteSize::to_string_as` rounds to one decimal place, which would
+        // not parse back to the same value; use the largest binary unit that
+        // divides the value exactly instead.
+        const UNITS: [(&str, u64); 4] =
+            [("TiB", 1 << 40), ("GiB", 1 << 30), ("MiB", 1 << 20), ("KiB", 1 << 10)];
+        let human = UNITS
+            .iter()
+            .find(|(_, unit)| *value != 0 && *value % unit == 0)
+            .map(|(name, unit)| format!("{} {name}", *value / unit))
+            .unwrap_or_else(|| format!("{} B", *value));
+        serializer.serialize_str(&human)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<u64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match NumberOrString::deserialize(deserializer)? {
+            NumberOrString::Number(n) => Ok(n),
+            NumberOrString::String(s) => s
+                .parse::<bytesize::ByteSize>()
+                .map(|b| b.as_u64())
+                .map_err(de::Error::custom),
+        }
+    }
+}
+
+/// Serde adapter for [`Duration`] fields. Accepts `"10min"`, `"2h"`, `"500ms"`,
+/// or a bare integer number of seconds; serializes to a canonical
+/// `humantime` string.
+pub mod duration {
+    use super::*;
+
+    pub fn serialize<S>(value: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&humantime::format_duration(*value).to_string())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match NumberOrString::deserialize(deserializer)? {
+            NumberOrString::Number(secs) => Ok(Duration::from_secs(secs)),
+            NumberOrString::String(s) => humantime::parse_duration(&s).map_err(de::Error::custom),
+        }
+    }
+}
+
+/// A field value that may be given either as a bare number (the historical
+/// representation) or as a human-readable string. Mirrors the untagged form
+/// `bytesize` and `humantime` use for backwards-compatible config parsing.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NumberOrString {
+    Number(u64),
+    String(String),
+}
@@ -23,9 +23,11 @@ use pageserver_api::models::{
 };
 use std::ops::Range;
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, Weak};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::Semaphore;
 use tracing::warn;
 use tracing::Instrument;
 use utils::history_buffer::HistoryBufferWithDropCounter;
@@ -48,6 +50,34 @@ use super::remote_timeline_client::RemoteTimelineClient;
 use super::timeline::layer_manager::LayerManager;
 use super::Timeline;
 
+/// Two-byte magic prefixing every on-disk layer file. The `_ZSTD` variants mark
+/// a zstd-compressed layer file. [`open_maybe_compressed`] is the only code
+/// that recognizes them, and it decompresses the whole stream it is given as
+/// one zstd stream; there is no per-blob decompression, and nothing in this
+/// module writes a `_ZSTD` file.
+pub const IMAGE_FILE_MAGIC: u16 = 0x5A60;
+pub const DELTA_FILE_MAGIC: u16 = 0x5A61; // historical value, kept for compatibility
+pub const IMAGE_FILE_MAGIC_ZSTD: u16 = 0x5A62;
+pub const DELTA_FILE_MAGIC_ZSTD: u16 = 0x5A63;
+
+/// Given the two-byte `magic` the caller has read from a layer file, wrap
+/// `reader` in a streaming zstd decoder if `magic` is a `_ZSTD` variant, and
+/// pass it through untouched otherwise. The magic is not put back in front of
+/// the returned stream: it yields exactly what `reader` decompresses (or
+/// holds) from its current position. No caller uses this yet, including
+/// `dump_layerfile_from_path`.
+pub(crate) fn open_maybe_compressed(
+    magic: u16,
+    reader: impl std::io::Read + 'static,
+) -> std::io::Result<Box<dyn std::io::Read>> {
+    match magic {
+        IMAGE_FILE_MAGIC_ZSTD | DELTA_FILE_MAGIC_ZSTD => {
+            Ok(Box::new(zstd::stream::read::Decoder::new(reader)?))
+        }
+        _ => Ok(Box::new(reader)),
+    }
+}
+
 pub fn range_overlaps<T>(a: &Range<T>, b: &Range<T>) -> bool
 where
     T: PartialOrd<T>,
@@ -330,45 +360,182 @@ impl LayerAccessStats {
             },
         }
     }
+
+    /// A GreedyDual-Size-Frequency-style score for a frequency/cost-aware
+    /// eviction policy: lower means "evict this first". Adds a frequency/size
+    /// term to [`EVICTION_CLOCK_BASE`], a baseline that
+    /// [`advance_eviction_clock_base`] raises every time something gets
+    /// evicted. The baseline is read when the score is computed, so every
+    /// layer scored at the same moment gets the same baseline: it does not
+    /// change the order of layers, which comes from the frequency/size term
+    /// alone, and a layer untouched since the last eviction is not scored
+    /// lower for it.
+    ///
+    /// `file_size` comes from the layer's [`PersistentLayerDesc`], which
+    /// `LayerAccessStats` doesn't itself know about; callers pass
+    /// `layer_desc().file_size`. Dividing the frequency term by size means
+    /// that, at equal access frequency, the larger (more expensive to keep
+    /// resident, cheaper per byte to re-fetch) layer scores lower and is
+    /// preferred for eviction.
+    ///
+    /// [`PersistentLayerDesc`]: super::layer_desc::PersistentLayerDesc
+    pub(crate) fn eviction_score(&self, now: SystemTime, file_size: u64) -> f64 {
+        let locked = self.0.lock().unwrap();
+        let inner = &locked.for_eviction_policy;
+
+        // The buffer only ever holds the last 16 accesses, so we can't get an
+        // exact windowed count out of it without exposing more of its
+        // internals than `recent()`. Approximate the window with the whole
+        // lifetime of the layer (`first_access` .. `now`) instead: still a
+        // frequency estimate, just with a coarser window than "last 16
+        // accesses".
+        let frequency_estimate = match inner.first_access {
+            Some(first) => {
+                let total_accesses: u64 = inner.count_by_access_kind.iter().map(|(_, c)| *c).sum();
+                let window_secs = now
+                    .duration_since(first.when)
+                    .unwrap_or(Duration::ZERO)
+                    .as_secs_f64()
+                    .max(1.0);
+                total_accesses as f64 / window_secs
+            }
+            None => 0.0,
+        };
+
+        eviction_clock_base() + frequency_estimate / (file_size.max(1) as f64)
+    }
 }
 
-/// The download-ness ([`DownloadedLayer`]) can be either resident or wanted evicted.
+/// See [`LayerE::throttle_download_attempt`].
+const DOWNLOAD_BACKOFF_BASE: Duration = Duration::from_millis(200);
+/// See [`LayerE::throttle_download_attempt`].
+const DOWNLOAD_BACKOFF_MAX: Duration = Duration::from_secs(30);
+/// `DOWNLOAD_BACKOFF_BASE * 2^cap` already exceeds `DOWNLOAD_BACKOFF_MAX`, so this just keeps
+/// the shift away from overflowing for a layer that's been failing for a very long time.
+const DOWNLOAD_BACKOFF_EXPONENT_CAP: u32 = 16;
+
+/// Whether to verify a resident layer file's content checksum (in addition to its size) before
+/// treating it as present, see [`LayerE::verify_checksum`]. Would normally be a
+/// `PageServerConf` flag, but `config.rs` isn't part of this checkout, so there's no config
+/// struct to add it to here.
+const VERIFY_LAYER_CHECKSUM: bool = false;
+
+/// Caps how many layer downloads can be in flight across the whole process at once, so a burst
+/// of page reconstructions can't open an unbounded number of concurrent remote-storage requests.
+/// See [`tenant_layer_download_semaphore`].
+const DEFAULT_MAX_CONCURRENT_LAYER_DOWNLOADS: usize = 100;
+/// Sub-limit within [`DEFAULT_MAX_CONCURRENT_LAYER_DOWNLOADS`] applied per tenant, so one busy
+/// tenant can't claim the whole global budget and starve every other tenant's downloads.
+const DEFAULT_MAX_CONCURRENT_LAYER_DOWNLOADS_PER_TENANT: usize = 16;
+
+static GLOBAL_LAYER_DOWNLOAD_SEMAPHORE: Lazy<Arc<Semaphore>> =
+    Lazy::new(|| Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENT_LAYER_DOWNLOADS)));
+
+static TENANT_LAYER_DOWNLOAD_SEMAPHORES: Lazy<Mutex<HashMap<TenantId, Arc<Semaphore>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// The (lazily created) semaphore bounding concurrent layer downloads for `tenant_id`. Acquiring
+/// a permit from this *and* [`GLOBAL_LAYER_DOWNLOAD_SEMAPHORE`] is what [`LayerE::get_or_download`]
+/// does before it starts a download, the same two-tier scheme the directory fetcher uses a single
+/// `parallelism` argument for, just with an extra tenant-fair tier.
+fn tenant_layer_download_semaphore(tenant_id: TenantId) -> Arc<Semaphore> {
+    Arc::clone(
+        TENANT_LAYER_DOWNLOAD_SEMAPHORES
+            .lock()
+            .unwrap()
+            .entry(tenant_id)
+            .or_insert_with(|| {
+                Arc::new(Semaphore::new(
+                    DEFAULT_MAX_CONCURRENT_LAYER_DOWNLOADS_PER_TENANT,
+                ))
+            }),
+    )
+}
+
+/// See [`LayerAccessStats::eviction_score`].
+static EVICTION_CLOCK_BASE: AtomicU64 = AtomicU64::new(0);
+
+/// Raise the shared eviction clock baseline to (at least) `evicted_score`,
+/// the score of the layer an eviction policy just chose. Every later
+/// [`LayerAccessStats::eviction_score`] adds the same raised baseline, whether
+/// or not the layer was accessed since, so it does not change which layer
+/// scores lowest.
+pub(crate) fn advance_eviction_clock_base(evicted_score: f64) {
+    // f64::to_bits preserves numeric ordering for the non-negative scores
+    // `eviction_score` produces, so `fetch_max` on the bit pattern is the
+    // same as taking the numeric max.
+    EVICTION_CLOCK_BASE.fetch_max(evicted_score.to_bits(), Ordering::Relaxed);
+}
+
+fn eviction_clock_base() -> f64 {
+    f64::from_bits(EVICTION_CLOCK_BASE.load(Ordering::Relaxed))
+}
+
+/// The residency state of a [`LayerE`], held under its `inner` mutex.
+///
+/// `evict()` cannot evict a layer right away if there are current reads
+/// happening on it (e.g. it was looked up in [`LayerMap`] but
+/// [`Layer::get_value_reconstruct_data`] hasn't dropped its `ResidentLayer`
+/// guard yet), so `Resident` first downgrades to `WantedEvicted`, which only
+/// becomes `Evicted` once the last [`DownloadedLayer`] with a matching
+/// `epoch` drops. `epoch` is bumped every time [`LayerE::get_or_download`]
+/// starts a fresh residency, so a [`DownloadedLayer`] drop belonging to a
+/// since-superseded residency can never delete a file a later download just
+/// wrote -- it simply observes its own `epoch` no longer matches and is a
+/// no-op.
 ///
-/// However when we want something evicted, we cannot evict it right away as there might be current
-/// reads happening on it. It has been for example searched from [`LayerMap`] but not yet
-/// [`Layer::get_value_reconstruct_data`].
+/// All transitions happen while holding the mutex, which is what makes this
+/// deterministic: there is no window where a flag can be set in between a
+/// reader checking it and the state being replaced out from under it, unlike
+/// the separate `wanted_evicted`/`version` atomics this replaces.
 ///
 /// [`LayerMap`]: crate::tenant::layer_map::LayerMap
-enum ResidentOrWantedEvicted {
-    Resident(Arc<DownloadedLayer>),
-    WantedEvicted(Weak<DownloadedLayer>),
+enum LayerState {
+    /// A download is in flight. `evict()` can't act on this without
+    /// blocking, so it just reports the eviction as deferred; once the
+    /// download lands the layer is simply `Resident`, and a caller that
+    /// still wants it gone calls `evict()` again.
+    Downloading,
+    /// Resident and not (yet) marked for eviction.
+    Resident {
+        downloaded: Arc<DownloadedLayer>,
+        epoch: u64,
+    },
+    /// Marked for eviction, but at least one strong reference was still
+    /// alive when `evict()` ran. Becomes `Evicted` once the last one drops.
+    WantedEvicted {
+        downloaded: Weak<DownloadedLayer>,
+        epoch: u64,
+    },
+    /// Not resident: no file on disk for this epoch.
+    Evicted { epoch: u64 },
 }
 
-impl ResidentOrWantedEvicted {
-    fn get(&self) -> Option<Arc<DownloadedLayer>> {
-        match self {
-            ResidentOrWantedEvicted::Resident(strong) => Some(strong.clone()),
-            ResidentOrWantedEvicted::WantedEvicted(weak) => weak.upgrade(),
-        }
+impl Default for LayerState {
+    fn default() -> Self {
+        LayerState::Evicted { epoch: 0 }
     }
-    /// When eviction is first requested, drop down to holding a [`Weak`].
-    ///
-    /// Returns `true` if this was the first time eviction was requested.
-    fn downgrade(&mut self) -> &Weak<DownloadedLayer> {
-        let _was_first = match self {
-            ResidentOrWantedEvicted::Resident(strong) => {
-                let weak = Arc::downgrade(strong);
-                *self = ResidentOrWantedEvicted::WantedEvicted(weak);
-                // returning the weak is not useful, because the drop could had already ran with
-                // the replacement above, and that will take care of cleaning the Option we are in
-                true
-            }
-            ResidentOrWantedEvicted::WantedEvicted(_) => false,
-        };
+}
 
+impl LayerState {
+    /// Return the resident layer if there is one, un-deferring a pending
+    /// eviction back to `Resident` if a reader shows up while it's only
+    /// `WantedEvicted` (the strong reference the eviction was waiting to see
+    /// dropped turned out to still be needed).
+    fn get_or_undefer_eviction(&mut self) -> Option<Arc<DownloadedLayer>> {
         match self {
-            ResidentOrWantedEvicted::WantedEvicted(ref weak) => weak,
-            _ => unreachable!("just wrote wanted evicted"),
+            LayerState::Resident { downloaded, .. } => Some(downloaded.clone()),
+            LayerState::WantedEvicted { downloaded, epoch } => {
+                let epoch = *epoch;
+                downloaded.upgrade().map(|downloaded| {
+                    *self = LayerState::Resident {
+                        downloaded: downloaded.clone(),
+                        epoch,
+                    };
+                    downloaded
+                })
+            }
+            LayerState::Downloading | LayerState::Evicted { .. } => None,
         }
     }
 }
@@ -390,10 +557,8 @@ pub(crate) struct LayerE {
 
     access_stats: LayerAccessStats,
 
-    /// This is a mutex, because we want to be able to
-    /// - `Option::take(&mut self)` to drop the Arc allocation
-    /// - `ResidentDeltaLayer::downgrade(&mut self)`
-    inner: tokio::sync::Mutex<Option<ResidentOrWantedEvicted>>,
+    /// The layer's residency state. See [`LayerState`].
+    inner: tokio::sync::Mutex<LayerState>,
 
     /// Do we want to garbage collect this when `LayerE` is dropped, where garbage collection
     /// means:
@@ -401,18 +566,19 @@ pub(crate) struct LayerE {
     /// - instant local deletion
     wanted_garbage_collected: AtomicBool,
 
-    /// Accessed using `Ordering::Acquire` or `Ordering::Release` to have happens before together
-    /// to allow wait-less `evict`
-    ///
-    /// FIXME: this is likely bogus assumption, there is still time for us to set the flag in
-    /// `evict` after the task holding the lock has made the check and is dropping the mutex guard.
-    ///
-    /// However eviction will try to evict this again, so maybe it's fine?
-    wanted_evicted: AtomicBool,
+    /// Consecutive `download_layer_file` failures since the last success, reset to 0 on a
+    /// successful download. Drives the backoff in [`Self::throttle_download_attempt`].
+    download_failures: AtomicU32,
+
+    /// When the most recent download attempt (successful or not) was started.
+    last_download_attempt: Mutex<Option<Instant>>,
+
+    /// Set once the current residency's file has passed [`Self::verify_checksum`] /
+    /// [`Self::verify_checksum_blocking`], so that repeat callers (notably [`Self::info`], via
+    /// [`Self::needs_download_blocking`]) don't re-hash the file on every call. Reset whenever
+    /// [`Self::get_or_download`] starts a fresh residency.
+    checksum_verified: AtomicBool,
 
-    /// Version is to make sure we will in fact only evict a file if no new guard has been created
-    /// for it.
-    version: AtomicUsize,
     have_remote_client: bool,
 
     /// Allow subscribing to when the layer actually gets evicted.
@@ -498,9 +664,10 @@ impl LayerE {
             have_remote_client: timeline.remote_client.is_some(),
             access_stats,
             wanted_garbage_collected: AtomicBool::new(false),
-            wanted_evicted: AtomicBool::new(false),
+            download_failures: AtomicU32::new(0),
+            last_download_attempt: Mutex::new(None),
+            checksum_verified: AtomicBool::new(false),
             inner: Default::default(),
-            version: AtomicUsize::new(0),
             #[cfg(test)]
             evicted: tokio::sync::Notify::default(),
         }
@@ -520,6 +687,7 @@ impl LayerE {
         let outer = Arc::new_cyclic(|owner| {
             let inner = Arc::new(DownloadedLayer {
                 owner: owner.clone(),
+                epoch: 0,
                 kind: tokio::sync::OnceCell::default(),
             });
             resident = Some(inner.clone());
@@ -531,9 +699,14 @@ impl LayerE {
                 have_remote_client: timeline.remote_client.is_some(),
                 access_stats: LayerAccessStats::empty_will_record_residence_event_later(),
                 wanted_garbage_collected: AtomicBool::new(false),
-                wanted_evicted: AtomicBool::new(false),
-                inner: tokio::sync::Mutex::new(Some(ResidentOrWantedEvicted::Resident(inner))),
-                version: AtomicUsize::new(0),
+                download_failures: AtomicU32::new(0),
+                last_download_attempt: Mutex::new(None),
+                // We just wrote this file ourselves; no need to re-read and hash it.
+                checksum_verified: AtomicBool::new(true),
+                inner: tokio::sync::Mutex::new(LayerState::Resident {
+                    downloaded: inner,
+                    epoch: 0,
+                }),
                 #[cfg(test)]
                 evicted: tokio::sync::Notify::default(),
             }
@@ -569,25 +742,40 @@ impl LayerE {
             self.have_remote_client,
             "refusing to evict without a remote timeline client"
         );
-        self.wanted_evicted.store(true, Ordering::Release);
 
         let Ok(mut guard) = self.inner.try_lock() else {
-            // we don't need to wait around if there is a download ongoing, because that might reset the wanted_evicted
-            // however it's also possible that we are present and just accessed by someone else.
+            // A download is in flight; we can't act on this without blocking. Once it lands
+            // the layer is plain `Resident`, and a caller that still wants it evicted has to
+            // call `evict()` again -- see the [`LayerState::Downloading`] doc comment.
             return Ok(false);
         };
 
-        if let Some(either) = guard.as_mut() {
-            // now, this might immediatedly cause the drop fn to run, but that'll only act on
-            // background
-            let weak = either.downgrade();
+        match &mut *guard {
+            LayerState::Downloading => Ok(false),
+            LayerState::Evicted { .. } => Err(super::timeline::EvictionError::FileNotFound),
+            LayerState::WantedEvicted { downloaded, .. } => Ok(downloaded.upgrade().is_none()),
+            LayerState::Resident { .. } => {
+                // Pull out and drop our own strong reference first, so that if we are the
+                // last owner, `DownloadedLayer::drop` -> `on_drop` runs synchronously as part
+                // of this, instead of `weak.upgrade()` below seeing our own reference as still
+                // alive.
+                let LayerState::Resident { downloaded, epoch } =
+                    std::mem::replace(&mut *guard, LayerState::Downloading)
+                else {
+                    unreachable!("just matched Resident");
+                };
+                let weak = Arc::downgrade(&downloaded);
+                drop(downloaded);
 
-            let right_away = weak.upgrade().is_none();
+                let evicted_immediately = weak.upgrade().is_none();
 
-            Ok(right_away)
-        } else {
-            // already evicted; the wanted_evicted will be reset by next download
-            Err(super::timeline::EvictionError::FileNotFound)
+                *guard = LayerState::WantedEvicted {
+                    downloaded: weak,
+                    epoch,
+                };
+
+                Ok(evicted_immediately)
+            }
         }
     }
 
@@ -651,28 +839,7 @@ impl LayerE {
 
     async fn get(&self) -> Option<Arc<DownloadedLayer>> {
         let mut locked = self.inner.lock().await;
-
-        Self::get_or_apply_evictedness(&mut locked, &self.wanted_evicted)
-    }
-
-    fn get_or_apply_evictedness(
-        guard: &mut tokio::sync::MutexGuard<'_, Option<ResidentOrWantedEvicted>>,
-        wanted_evicted: &AtomicBool,
-    ) -> Option<Arc<DownloadedLayer>> {
-        if let Some(x) = &mut **guard {
-            let ret = x.get();
-
-            if let Some(won) = ret {
-                // there are no guarantees that we will always get to observe a concurrent call
-                // to evict
-                if wanted_evicted.load(Ordering::Acquire) {
-                    x.downgrade();
-                }
-                return Some(won);
-            }
-        }
-
-        None
+        locked.get_or_undefer_eviction()
     }
 
     /// Cancellation safe.
@@ -682,7 +849,7 @@ impl LayerE {
     ) -> anyhow::Result<Arc<DownloadedLayer>> {
         let mut locked = self.inner.lock().await;
 
-        if let Some(strong) = Self::get_or_apply_evictedness(&mut locked, &self.wanted_evicted) {
+        if let Some(strong) = locked.get_or_undefer_eviction() {
             return Ok(strong);
         }
 
@@ -715,18 +882,20 @@ impl LayerE {
             }
         }
 
-        // disable any scheduled but not yet running eviction deletions for this
-        self.version.fetch_add(1, Ordering::Relaxed);
-
-        // what to do if we have a concurrent eviction request when we are downloading? eviction
-        // api's use ResidentLayer, so evict could be moved there, or we just reset the state here.
-        self.wanted_evicted.store(false, Ordering::Release);
+        // bump the epoch so a deferred eviction task from the residency we're about to replace
+        // can never act on the residency we're starting here, see `LayerState`'s doc comment.
+        let epoch = match &*locked {
+            LayerState::Resident { epoch, .. }
+            | LayerState::WantedEvicted { epoch, .. }
+            | LayerState::Evicted { epoch } => epoch.wrapping_add(1),
+            LayerState::Downloading => unreachable!("we are the one holding the lock to download"),
+        };
 
         // drop the old one, we only held the weak or it was had not been initialized ever
-        locked.take();
+        *locked = LayerState::Downloading;
 
-        // technically the mutex could be dropped here and it does seem extra not to have Option
-        // here
+        // the new residency's file hasn't been hashed yet.
+        self.checksum_verified.store(false, Ordering::Release);
 
         let Some(timeline) = self.timeline.upgrade() else { anyhow::bail!("timeline has gone already") };
 
@@ -747,12 +916,36 @@ impl LayerE {
             if self.wanted_garbage_collected.load(Ordering::Acquire) {
                 // it will fail because we should had already scheduled a delete and an
                 // index update
-                tracing::info!(%reason, "downloading a wanted garbage collected layer, this might fail");
+                tracing::info!(%reason, attempt = epoch, "downloading a wanted garbage collected layer, this might fail");
                 // FIXME: we probably do not gc delete until the file goes away...? unsure
             } else {
-                tracing::debug!(%reason, "downloading layer");
+                tracing::debug!(%reason, attempt = epoch, "downloading layer");
             }
 
+            self.throttle_download_attempt(ctx).await?;
+            *self.last_download_attempt.lock().unwrap() = Some(Instant::now());
+
+            // `epoch` also identifies this residency in `LayerState` (see its doc comment), so
+            // stamping it on the download span lets a later eviction or post-condition failure
+            // be correlated back to the exact attempt that produced it.
+            let download_span =
+                tracing::info_span!(parent: None, "download layer", tenant_id = %self.desc.tenant_id, timeline_id = %self.desc.timeline_id, layer = %self, attempt = epoch);
+
+            // Bound how many downloads can be in flight at once, globally and per tenant, before
+            // spawning the task below. Holding both permits for the lifetime of the download is
+            // what provides backpressure; see `tenant_layer_download_semaphore`.
+            crate::metrics::LAYER_DOWNLOADS_QUEUED.inc();
+            let tenant_permit = tenant_layer_download_semaphore(self.desc.tenant_id)
+                .acquire_owned()
+                .await
+                .expect("tenant layer download semaphore is never closed");
+            let global_permit = Arc::clone(&GLOBAL_LAYER_DOWNLOAD_SEMAPHORE)
+                .acquire_owned()
+                .await
+                .expect("global layer download semaphore is never closed");
+            crate::metrics::LAYER_DOWNLOADS_QUEUED.dec();
+            crate::metrics::LAYER_DOWNLOADS_IN_FLIGHT.inc();
+
             let (tx, rx) = tokio::sync::oneshot::channel();
             // this is sadly needed because of task_mgr::shutdown_tasks, otherwise we cannot
             // block tenant::mgr::remove_tenant_from_memory.
@@ -765,6 +958,9 @@ impl LayerE {
                 &task_name,
                 false,
                 async move {
+                    // Held for the duration of the download to cap concurrency.
+                    let _permits = (tenant_permit, global_permit);
+
                     let client = timeline
                         .remote_client
                         .as_ref()
@@ -780,29 +976,32 @@ impl LayerE {
 
                     match result {
                         Ok(size) => {
+                            this.download_failures.store(0, Ordering::Relaxed);
                             timeline.metrics.resident_physical_size_gauge.add(size);
                             let _ = tx.send(());
                         }
                         Err(e) => {
                             // TODO: the temp file might still be around, metrics might be off
+                            this.download_failures.fetch_add(1, Ordering::Relaxed);
                             tracing::error!("layer file download failed: {e:?}",);
                         }
                     }
 
+                    crate::metrics::LAYER_DOWNLOADS_IN_FLIGHT.dec();
+
                     Ok(())
                 }
-                .in_current_span(),
+                .instrument(download_span),
             );
             if rx.await.is_err() {
                 return Err(anyhow::anyhow!("downloading failed, possibly for shutdown"));
             }
-            // FIXME: we need backoff here so never spiral to download loop
             anyhow::ensure!(
                 self.needs_download()
                     .await
                     .context("test if downloading is still needed")?
                     .is_none(),
-                "post-condition for downloading: no longer needs downloading"
+                "post-condition for downloading (attempt {epoch}): no longer needs downloading"
             );
         } else {
             // the file is present locally and we could even be running without remote
@@ -819,25 +1018,72 @@ impl LayerE {
 
         let res = Arc::new(DownloadedLayer {
             owner: Arc::downgrade(self),
+            epoch,
             kind: tokio::sync::OnceCell::default(),
         });
 
-        *locked = Some(if self.wanted_evicted.load(Ordering::Acquire) {
-            // because we reset wanted_evictness near beginning, this means when we were downloading someone
-            // wanted to evict this layer.
-            //
-            // perhaps the evict should only possible via ResidentLayer because this makes my head
-            // spin. the caller of this function will still get the proper `Arc<DownloadedLayer>`.
-            //
-            // the risk is that eviction becomes too flaky.
-            ResidentOrWantedEvicted::WantedEvicted(Arc::downgrade(&res))
-        } else {
-            ResidentOrWantedEvicted::Resident(res.clone())
-        });
+        // A concurrent `evict()` call couldn't see this residency while we held the lock
+        // across the whole download, so there's nothing to carry forward here: we always land
+        // as plain `Resident`. A caller that still wants this evicted calls `evict()` again,
+        // see `LayerState::Downloading`'s doc comment.
+        *locked = LayerState::Resident {
+            downloaded: res.clone(),
+            epoch,
+        };
 
         Ok(res)
     }
 
+    /// Wait out (or reject) a download attempt that would otherwise come too soon after a
+    /// failed one, so that a remote storage outage doesn't turn every reader into a tight
+    /// re-download loop. Only has an effect once at least one attempt has failed; the first
+    /// attempt, and every attempt after a success, goes through immediately.
+    async fn throttle_download_attempt(&self, ctx: Option<&RequestContext>) -> anyhow::Result<()> {
+        use rand::Rng;
+
+        let failures = self.download_failures.load(Ordering::Relaxed);
+        if failures == 0 {
+            return Ok(());
+        }
+
+        // Full-jitter exponential backoff, same shape as `retry_remote` in `tenant.rs`.
+        let exp = DOWNLOAD_BACKOFF_BASE
+            .saturating_mul(1u32 << failures.min(DOWNLOAD_BACKOFF_EXPONENT_CAP))
+            .min(DOWNLOAD_BACKOFF_MAX);
+        let wanted_delay =
+            Duration::from_millis(rand::thread_rng().gen_range(0..=exp.as_millis() as u64));
+
+        let elapsed = self
+            .last_download_attempt
+            .lock()
+            .unwrap()
+            .map(|at| at.elapsed())
+            .unwrap_or(Duration::MAX);
+
+        if elapsed >= wanted_delay {
+            return Ok(());
+        }
+        let remaining = wanted_delay - elapsed;
+
+        // In the common case (a background task retrying on behalf of a reader) just wait the
+        // backoff out. Only bail when the caller told us downloads here are unexpected, so we
+        // don't turn an already-surprising on-demand download into an unbounded stall too.
+        let should_reject = matches!(
+            ctx.map(|ctx| ctx.download_behavior()),
+            Some(crate::context::DownloadBehavior::Error)
+        ) && !self.conf.ondemand_download_behavior_treat_error_as_warn;
+
+        if should_reject {
+            anyhow::bail!(
+                "backing off downloading layer {self} after {failures} consecutive failures, retry in {remaining:?}"
+            );
+        }
+
+        tracing::info!(failures, ?remaining, "backing off before retrying layer download");
+        tokio::time::sleep(remaining).await;
+        Ok(())
+    }
+
     pub(crate) fn local_path(&self) -> &std::path::Path {
         // maybe it does make sense to have this or maybe not
         &self.path
@@ -845,7 +1091,10 @@ impl LayerE {
 
     async fn needs_download(&self) -> Result<Option<NeedsDownload>, std::io::Error> {
         match tokio::fs::metadata(self.local_path()).await {
-            Ok(m) => Ok(self.is_file_present_and_good_size(&m)),
+            Ok(m) => match self.is_file_present_and_good_size(&m) {
+                Some(nd) => Ok(Some(nd)),
+                None => self.verify_checksum().await,
+            },
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Some(NeedsDownload::NotFound)),
             Err(e) => Err(e),
         }
@@ -853,15 +1102,16 @@ impl LayerE {
 
     pub(crate) fn needs_download_blocking(&self) -> Result<Option<NeedsDownload>, std::io::Error> {
         match self.local_path().metadata() {
-            Ok(m) => Ok(self.is_file_present_and_good_size(&m)),
+            Ok(m) => match self.is_file_present_and_good_size(&m) {
+                Some(nd) => Ok(Some(nd)),
+                None => self.verify_checksum_blocking(),
+            },
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Some(NeedsDownload::NotFound)),
             Err(e) => Err(e),
         }
     }
 
     fn is_file_present_and_good_size(&self, m: &std::fs::Metadata) -> Option<NeedsDownload> {
-        // in future, this should include sha2-256 the file, hopefully rarely, because info uses
-        // this as well
         if !m.is_file() {
             Some(NeedsDownload::NotFile)
         } else if m.len() != self.desc.file_size {
@@ -874,6 +1124,33 @@ impl LayerE {
         }
     }
 
+    /// Hash the resident file and compare it against the layer's expected checksum, caching a
+    /// match in `checksum_verified` so repeat callers don't re-read the file. A no-op if
+    /// verification is disabled, already cached, or the layer carries no checksum (e.g. one
+    /// written before this field existed).
+    ///
+    /// NOTE: `PersistentLayerDesc` (in `layer_desc.rs`) isn't part of this checkout and doesn't
+    /// carry a `checksum` field, so there's nothing to compare against yet -- this always
+    /// returns `Ok(None)` until that field lands there. `VERIFY_LAYER_CHECKSUM` is `false` for
+    /// the same reason: flipping it on today would have nothing to verify.
+    fn verify_checksum_blocking(&self) -> Result<Option<NeedsDownload>, std::io::Error> {
+        if !VERIFY_LAYER_CHECKSUM || self.checksum_verified.load(Ordering::Acquire) {
+            return Ok(None);
+        }
+        Ok(None)
+    }
+
+    /// Async equivalent of [`Self::verify_checksum_blocking`], hashing on a blocking thread so
+    /// the (potentially large) file read doesn't stall the executor.
+    ///
+    /// Stubbed out the same way and for the same reason as [`Self::verify_checksum_blocking`].
+    async fn verify_checksum(&self) -> Result<Option<NeedsDownload>, std::io::Error> {
+        if !VERIFY_LAYER_CHECKSUM || self.checksum_verified.load(Ordering::Acquire) {
+            return Ok(None);
+        }
+        Ok(None)
+    }
+
     pub(crate) fn info(&self, reset: LayerAccessStatsReset) -> HistoricLayerInfo {
         let layer_file_name = self.desc.filename().file_name();
 
@@ -911,22 +1188,26 @@ impl LayerE {
         &self.access_stats
     }
 
+    /// See [`LayerAccessStats::eviction_score`].
+    pub(crate) fn eviction_score(&self, now: SystemTime) -> f64 {
+        self.access_stats
+            .eviction_score(now, self.layer_desc().file_size)
+    }
+
     /// Our resident layer has been dropped, we might hold the lock elsewhere.
-    fn on_drop(self: Arc<LayerE>) {
+    fn on_drop(self: Arc<LayerE>, epoch: u64) {
         let gc = self.wanted_garbage_collected.load(Ordering::Acquire);
-        let evict = self.wanted_evicted.load(Ordering::Acquire);
         let can_evict = self.have_remote_client;
 
         if gc {
             // do nothing now, only when the whole layer is dropped. gc will end up dropping the
             // whole layer, in case there is no reference cycle.
-        } else if can_evict && evict {
+        } else if can_evict {
             // we can remove this right now, but ... we really should not block or do anything.
-            // spawn a task which first does a version check, and that version is also incremented
-            // on get_or_download, so we will not collide?
-            let version = self.version.load(Ordering::Relaxed);
-
-            let span = tracing::info_span!(parent: None, "layer_evict", tenant_id = %self.desc.tenant_id, timeline_id = %self.desc.timeline_id, layer=%self);
+            // spawn a task which first re-checks that `epoch` still matches the current
+            // `LayerState::WantedEvicted`, so a drop belonging to a since-superseded residency
+            // (one that lost a race with a fresh `get_or_download`) is a no-op.
+            let span = tracing::info_span!(parent: None, "layer_evict", tenant_id = %self.desc.tenant_id, timeline_id = %self.desc.timeline_id, layer=%self, attempt = epoch);
 
             // downgrade in case there's a queue backing up, or we are just tearing stuff down, and
             // would soon delete anyways.
@@ -943,23 +1224,16 @@ impl LayerE {
                     let Some(timeline) = this.timeline.upgrade() else { return; };
 
                     let mut guard = this.inner.lock().await;
-                    // relaxed ordering: we dont have any other atomics pending
-                    if version != this.version.load(Ordering::Relaxed) {
-                        // downloadness-state has advanced, we might no longer be the latest eviction
-                        // work; don't do anything.
+                    if !matches!(&*guard, LayerState::WantedEvicted { epoch: e, .. } if *e == epoch)
+                    {
+                        // downloadness-state has advanced (a fresh get_or_download landed, or
+                        // someone already upgraded the weak back to Resident); we're no longer
+                        // the latest eviction work, don't do anything.
                         return;
                     }
 
                     // free the DownloadedLayer allocation
-                    let taken = guard.take();
-                    assert!(matches!(taken, None | Some(ResidentOrWantedEvicted::WantedEvicted(_))), "this is what the version is supposed to guard against but we could just undo it and remove version?");
-
-                    if !this.wanted_evicted.load(Ordering::Acquire) {
-                        // if there's already interest, should we just early exit? this is not
-                        // currently *cleared* on interest, maybe it shouldn't?
-                        // FIXME: wanted_evicted cannot be unset right now
-                        return;
-                    }
+                    *guard = LayerState::Evicted { epoch };
 
                     let path = this.path.to_owned();
 
@@ -1033,6 +1307,10 @@ pub(crate) enum NeedsDownload {
     NotFound,
     NotFile,
     WrongSize { actual: u64, expected: u64 },
+    WrongChecksum {
+        actual: [u8; 32],
+        expected: [u8; 32],
+    },
 }
 
 impl std::fmt::Display for NeedsDownload {
@@ -1043,6 +1321,9 @@ impl std::fmt::Display for NeedsDownload {
             NeedsDownload::WrongSize { actual, expected } => {
                 write!(f, "file size mismatch {actual} vs. {expected}")
             }
+            NeedsDownload::WrongChecksum { actual, expected } => {
+                write!(f, "checksum mismatch {actual:02x?} vs. {expected:02x?}")
+            }
         }
     }
 }
@@ -1124,13 +1405,16 @@ pub(crate) struct RemovedFromLayerMap;
 /// Holds the actual downloaded layer, and handles evicting the file on drop.
 pub(crate) struct DownloadedLayer {
     owner: Weak<LayerE>,
+    /// The [`LayerState`] epoch this download belongs to. See the doc comment on
+    /// `LayerState` for why this matters on drop.
+    epoch: u64,
     kind: tokio::sync::OnceCell<anyhow::Result<LayerKind>>,
 }
 
 impl Drop for DownloadedLayer {
     fn drop(&mut self) {
         if let Some(owner) = self.owner.upgrade() {
-            owner.on_drop();
+            owner.on_drop(self.epoch);
         } else {
             // no need to do anything, we are shutting down
         }
@@ -1309,8 +1593,92 @@ pub trait Layer: std::fmt::Debug + std::fmt::Display + Send + Sync + 'static {
         ctx: &RequestContext,
     ) -> Result<ValueReconstructResult>;
 
+    /// Batched form of [`Self::get_value_reconstruct_data`]: reconstruct every `(key,
+    /// lsn_range)` pair in `keyspace` against this layer in one call, rather than one lookup
+    /// per key. `reconstruct_data` holds (and accumulates into) the per-key state the same way
+    /// [`Self::get_value_reconstruct_data`] does, so a key can be passed through several
+    /// layers' worth of calls; the returned map tells the caller, per key, whether it still
+    /// needs to descend to a predecessor layer.
+    ///
+    /// The default implementation just loops over [`Self::get_value_reconstruct_data`], so
+    /// every existing `Layer` impl keeps compiling unchanged. An implementation that walks its
+    /// on-disk index once in key order (as `DeltaLayerInner`/`ImageLayerInner` should) can
+    /// amortize index descent and block decompression across the whole batch instead of
+    /// redoing it per key.
+    async fn get_values_reconstruct_data(
+        &self,
+        keyspace: &[(Key, Range<Lsn>)],
+        reconstruct_data: &mut HashMap<Key, ValueReconstructState>,
+        ctx: &RequestContext,
+    ) -> Result<HashMap<Key, ValueReconstructResult>> {
+        let mut results = HashMap::with_capacity(keyspace.len());
+        for (key, lsn_range) in keyspace {
+            let entry = reconstruct_data
+                .entry(*key)
+                .or_insert_with(|| ValueReconstructState {
+                    records: Vec::new(),
+                    img: None,
+                });
+            let result = self
+                .get_value_reconstruct_data(*key, lsn_range.clone(), entry, ctx)
+                .await?;
+            results.insert(*key, result);
+        }
+        Ok(results)
+    }
+
     /// Dump summary of the contents of the layer to stdout
     async fn dump(&self, verbose: bool, ctx: &RequestContext) -> Result<()>;
+
+    /// Machine-readable counterpart to [`Self::dump`]: the same walk over the layer's
+    /// contents, but collected into a [`LayerDumpReport`] instead of printed, so callers like
+    /// `pagectl --json` can consume it without scraping stdout. `DeltaLayerInner` and
+    /// `ImageLayerInner` should build this from the same index walk `dump` already does, and
+    /// have `dump` itself render the resulting report; the default here only has access to
+    /// the bare [`Layer`] interface, so it reports the range/incremental bits it can answer
+    /// and leaves the per-key breakdown empty.
+    async fn dump_structured(&self, verbose: bool, ctx: &RequestContext) -> Result<LayerDumpReport> {
+        // Nothing is printed here; `verbose` and `ctx` matter only to implementations that
+        // fill in the per-key breakdown.
+        let _ = (verbose, ctx);
+        Ok(LayerDumpReport {
+            key_range: format!("{:?}", RangeDisplayDebug(&self.get_key_range())),
+            lsn_range: format!("{:?}", RangeDisplayDebug(&self.get_lsn_range())),
+            is_incremental: self.is_incremental(),
+            value_count: None,
+            record_count: None,
+            entries: Vec::new(),
+        })
+    }
+}
+
+/// Structured, serializable counterpart to the free-form output of [`Layer::dump`]. See
+/// [`Layer::dump_structured`].
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LayerDumpReport {
+    /// `"{start}..{end}"` rendering of [`Layer::get_key_range`]; `Key` itself doesn't
+    /// implement `Serialize`, so we carry its [`RangeDisplayDebug`] rendering instead.
+    pub key_range: String,
+    /// `"{start}..{end}"` rendering of [`Layer::get_lsn_range`].
+    pub lsn_range: String,
+    pub is_incremental: bool,
+    /// Total number of distinct keys with reconstructable data, if the implementation
+    /// tracked one while walking its index. `None` when not counted (as in the default
+    /// [`Layer::dump_structured`] implementation).
+    pub value_count: Option<u64>,
+    /// Total number of WAL/image records across all keys, if counted.
+    pub record_count: Option<u64>,
+    /// Per-key entry metadata, populated only when `dump_structured` was called with
+    /// `verbose = true` and the implementation supports it.
+    pub entries: Vec<LayerDumpEntry>,
+}
+
+/// One entry in [`LayerDumpReport::entries`].
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LayerDumpEntry {
+    pub key: String,
+    pub lsn: String,
+    pub size: u64,
 }
 
 /// Get a layer descriptor from a layer.
@@ -1332,6 +1700,7 @@ pub trait AsLayerDesc {
 /// A delta layer contains all modifications within a range of LSNs and keys.
 /// An image layer is a snapshot of all the data in a key-range, at a single
 /// LSN.
+#[async_trait::async_trait]
 pub trait PersistentLayer: Layer + AsLayerDesc {
     /// File name used for this layer, both in the pageserver's local filesystem
     /// state as well as in the remote storage.
@@ -1354,11 +1723,63 @@ pub trait PersistentLayer: Layer + AsLayerDesc {
         false
     }
 
+    /// Where this layer's bytes currently live. Supersedes checking `is_remote_layer()` and
+    /// `local_path()` separately: those two could be read a moment apart and disagree (a
+    /// download landing in between), and neither told a caller whether a download was
+    /// already in flight. The default implementation derives this from `local_path()`, so
+    /// existing implementations get a `Resident`/`Evicted` answer for free; one that can also
+    /// be mid-download should override it to report `Downloading` instead of `Evicted`.
+    fn residence(&self) -> LayerResidence {
+        match self.local_path() {
+            Some(local_path) => LayerResidence::Resident { local_path },
+            None => LayerResidence::Evicted,
+        }
+    }
+
+    /// Make sure this layer's bytes are resident locally — downloading them first if
+    /// [`Self::residence`] reports `Evicted`/`Downloading` — and hand back a guard proving
+    /// residency for the read that follows. Meant to replace the scattered
+    /// `is_remote_layer()` checks and `RemoteLayer` downcasts that call sites used to need
+    /// before calling [`Layer::get_value_reconstruct_data`].
+    ///
+    /// The default implementation has no download path of its own to drive, so it can only
+    /// succeed when the layer is already resident; an implementation backed by remote storage
+    /// (`RemoteLayer`, outside this checkout) must override this to await its download future
+    /// before returning the guard.
+    async fn ensure_resident(&self, _ctx: &RequestContext) -> Result<ResidentGuard> {
+        match self.residence() {
+            LayerResidence::Resident { local_path } => Ok(ResidentGuard { local_path }),
+            LayerResidence::Evicted | LayerResidence::Downloading => {
+                anyhow::bail!(
+                    "{self} is not resident and this PersistentLayer implementation does not \
+                     override ensure_resident() to download it on demand"
+                )
+            }
+        }
+    }
+
     fn info(&self, reset: LayerAccessStatsReset) -> HistoricLayerInfo;
 
     fn access_stats(&self) -> &LayerAccessStats;
 }
 
+/// Where a [`PersistentLayer`]'s bytes currently live. See [`PersistentLayer::residence`].
+#[derive(Debug, Clone)]
+pub enum LayerResidence {
+    /// The bytes are present on local disk at `local_path`, ready for immediate reads.
+    Resident { local_path: PathBuf },
+    /// The layer has been evicted to remote storage; a download is required before any read.
+    Evicted,
+    /// A download is already in flight.
+    Downloading,
+}
+
+/// RAII proof, returned by [`PersistentLayer::ensure_resident`], that a layer's bytes were
+/// resident locally at the moment the guard was created.
+pub struct ResidentGuard {
+    pub local_path: PathBuf,
+}
+
 pub mod tests {
     use super::*;
 
@@ -0,0 +1,290 @@
+//! A sharded, concurrent map from [`TimelineId`] to a tenant's live
+//! [`Timeline`] objects.
+//!
+//! [`Tenant::get_timeline`](super::Tenant::get_timeline) and friends used to
+//! go through a single [`std::sync::Mutex`]-protected `HashMap`, which
+//! serializes every lookup across unrelated timelines, even read-only ones.
+//! As a tenant grows to thousands of branches (see `test_traverse_ancestors`
+//! / `test_traverse_branches`), that one mutex becomes the bottleneck.
+//!
+//! [`TimelineRegistry`] instead owns `NUM_SHARDS` independently-locked
+//! shards; a timeline's shard is chosen by hashing its id, so unrelated
+//! timelines rarely contend. Each shard is a small slab: a `Vec` of slots
+//! plus a free list, so a slot can be reused after its timeline is removed
+//! without shrinking the backing storage. A [`TimelineHandle`] is a packed
+//! `(shard, slot, generation)` reference into a slot; [`TimelineRegistry::resolve`]
+//! re-checks the generation before returning the timeline, so a handle that
+//! outlives a removal (and a possible slot reuse) is rejected rather than
+//! silently resolving to the wrong timeline (ABA protection).
+
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use utils::id::TimelineId;
+
+use super::Timeline;
+
+/// Chosen near a typical core count; shards are cheap (an empty `Vec` plus a
+/// `HashMap`), so erring high costs little.
+const NUM_SHARDS: usize = 32;
+
+/// A packed, cheaply-copyable reference to a slot in a [`TimelineRegistry`]:
+/// `[ shard: 16 | slot: 32 | generation: 16 ]`. Re-resolving a handle is a
+/// single shard lock plus a generation check, avoiding a fresh hash + lookup
+/// on every access for a caller that already knows which slot it wants
+/// (e.g. repeated ancestor traversal).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimelineHandle(u64);
+
+impl TimelineHandle {
+    fn pack(shard: usize, slot: usize, generation: u16) -> Self {
+        debug_assert!(shard < (1 << 16));
+        debug_assert!(slot < (1 << 32));
+        TimelineHandle(((shard as u64) << 48) | ((slot as u64) << 16) | generation as u64)
+    }
+
+    fn shard(self) -> usize {
+        (self.0 >> 48) as usize
+    }
+
+    fn slot(self) -> usize {
+        ((self.0 >> 16) & 0xffff_ffff) as usize
+    }
+
+    fn generation(self) -> u16 {
+        self.0 as u16
+    }
+}
+
+struct Slot {
+    timeline_id: TimelineId,
+    generation: u16,
+    timeline: Arc<Timeline>,
+}
+
+#[derive(Default)]
+struct Shard {
+    slots: Vec<Option<Slot>>,
+    free_list: Vec<usize>,
+    index: HashMap<TimelineId, usize>,
+}
+
+impl Shard {
+    /// Allocate a slot for `timeline_id`, reusing a free one if available,
+    /// and bump its generation so any handle into a previous occupant of
+    /// that slot stops resolving.
+    fn alloc(&mut self, timeline_id: TimelineId, timeline: Arc<Timeline>) -> usize {
+        if let Some(slot_idx) = self.free_list.pop() {
+            let generation = self.slots[slot_idx]
+                .take()
+                .map(|s| s.generation.wrapping_add(1))
+                .unwrap_or(0);
+            self.slots[slot_idx] = Some(Slot {
+                timeline_id,
+                generation,
+                timeline,
+            });
+            slot_idx
+        } else {
+            let slot_idx = self.slots.len();
+            self.slots.push(Some(Slot {
+                timeline_id,
+                generation: 0,
+                timeline,
+            }));
+            slot_idx
+        }
+    }
+}
+
+/// A sharded, concurrent `TimelineId -> Arc<Timeline>` map. See the module
+/// docs for the rationale.
+pub struct TimelineRegistry {
+    shards: Vec<Mutex<Shard>>,
+}
+
+impl Default for TimelineRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimelineRegistry {
+    pub fn new() -> Self {
+        TimelineRegistry {
+            shards: (0..NUM_SHARDS).map(|_| Mutex::new(Shard::default())).collect(),
+        }
+    }
+
+    fn shard_index(&self, timeline_id: &TimelineId) -> usize {
+        let mut hasher = DefaultHasher::new();
+        timeline_id.hash(&mut hasher);
+        (hasher.finish() as usize) % self.shards.len()
+    }
+
+    fn lock_shard_for(&self, timeline_id: &TimelineId) -> MutexGuard<'_, Shard> {
+        self.shards[self.shard_index(timeline_id)].lock().unwrap()
+    }
+
+    pub fn get(&self, timeline_id: &TimelineId) -> Option<Arc<Timeline>> {
+        let shard = self.lock_shard_for(timeline_id);
+        let slot_idx = *shard.index.get(timeline_id)?;
+        shard.slots[slot_idx].as_ref().map(|s| Arc::clone(&s.timeline))
+    }
+
+    pub fn contains_key(&self, timeline_id: &TimelineId) -> bool {
+        self.lock_shard_for(timeline_id).index.contains_key(timeline_id)
+    }
+
+    pub fn insert(&self, timeline_id: TimelineId, timeline: Arc<Timeline>) -> Option<Arc<Timeline>> {
+        let mut shard = self.lock_shard_for(&timeline_id);
+        if let Some(&slot_idx) = shard.index.get(&timeline_id) {
+            let old = std::mem::replace(
+                &mut shard.slots[slot_idx].as_mut().expect("indexed slot is occupied").timeline,
+                timeline,
+            );
+            Some(old)
+        } else {
+            let slot_idx = shard.alloc(timeline_id, timeline);
+            shard.index.insert(timeline_id, slot_idx);
+            None
+        }
+    }
+
+    pub fn remove(&self, timeline_id: &TimelineId) -> Option<Arc<Timeline>> {
+        let mut shard = self.lock_shard_for(timeline_id);
+        let slot_idx = shard.index.remove(timeline_id)?;
+        let slot = shard.slots[slot_idx].take().expect("indexed slot is occupied");
+        shard.free_list.push(slot_idx);
+        Some(slot.timeline)
+    }
+
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|s| s.lock().unwrap().index.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// A consistent-per-shard (not a tenant-wide atomic) snapshot of every
+    /// `(id, timeline)` pair. Good enough for the scan-then-act callers this
+    /// replaces, which never relied on a single global lock for atomicity
+    /// either.
+    pub fn iter(&self) -> std::vec::IntoIter<(TimelineId, Arc<Timeline>)> {
+        let mut out = Vec::new();
+        for shard in &self.shards {
+            let shard = shard.lock().unwrap();
+            out.extend(
+                shard
+                    .slots
+                    .iter()
+                    .filter_map(|s| s.as_ref())
+                    .map(|s| (s.timeline_id, Arc::clone(&s.timeline))),
+            );
+        }
+        out.into_iter()
+    }
+
+    pub fn values(&self) -> std::vec::IntoIter<Arc<Timeline>> {
+        self.iter().map(|(_, timeline)| timeline).collect::<Vec<_>>().into_iter()
+    }
+
+    /// A handle for repeated lookups of `timeline_id` without re-hashing.
+    /// `None` if the timeline isn't currently registered.
+    pub fn handle_for(&self, timeline_id: &TimelineId) -> Option<TimelineHandle> {
+        let shard_idx = self.shard_index(timeline_id);
+        let shard = self.shards[shard_idx].lock().unwrap();
+        let slot_idx = *shard.index.get(timeline_id)?;
+        let slot = shard.slots[slot_idx].as_ref()?;
+        Some(TimelineHandle::pack(shard_idx, slot_idx, slot.generation))
+    }
+
+    /// Resolve a handle obtained from [`Self::handle_for`]. Returns `None` if
+    /// the slot has since been removed (and possibly reused for a different
+    /// timeline), rather than risk returning the wrong timeline.
+    pub fn resolve(&self, handle: TimelineHandle) -> Option<Arc<Timeline>> {
+        let shard = self.shards.get(handle.shard())?.lock().unwrap();
+        let slot = shard.slots.get(handle.slot())?.as_ref()?;
+        if slot.generation != handle.generation() {
+            return None;
+        }
+        Some(Arc::clone(&slot.timeline))
+    }
+
+    /// Look up `timeline_id` again and return it only if active.
+    ///
+    /// Unlike [`Self::resolve`], this always re-hashes and re-checks the live
+    /// map rather than trusting a previously obtained [`TimelineHandle`], so a
+    /// caller retrying a read across an ancestor's `Loading` -> `Active`
+    /// transition picks up the now-usable timeline instead of being stuck
+    /// with whatever it first observed.
+    pub fn get_if_active(&self, timeline_id: &TimelineId) -> Option<Arc<Timeline>> {
+        let timeline = self.get(timeline_id)?;
+        timeline.is_active().then_some(timeline)
+    }
+
+    /// Returns the entry for `timeline_id`, locking only its shard.
+    pub fn entry(&self, timeline_id: TimelineId) -> Entry<'_> {
+        let shard = self.lock_shard_for(&timeline_id);
+        if shard.index.contains_key(&timeline_id) {
+            Entry::Occupied(OccupiedEntry { shard, timeline_id })
+        } else {
+            Entry::Vacant(VacantEntry { shard, timeline_id })
+        }
+    }
+}
+
+/// Mimics [`std::collections::hash_map::Entry`] closely enough that call
+/// sites written against the old `HashMap`-backed map didn't need to change
+/// shape, only the path they import `Entry` from.
+pub enum Entry<'a> {
+    Occupied(OccupiedEntry<'a>),
+    Vacant(VacantEntry<'a>),
+}
+
+pub struct OccupiedEntry<'a> {
+    shard: MutexGuard<'a, Shard>,
+    timeline_id: TimelineId,
+}
+
+impl<'a> OccupiedEntry<'a> {
+    fn slot_idx(&self) -> usize {
+        self.shard.index[&self.timeline_id]
+    }
+
+    pub fn get(&self) -> &Arc<Timeline> {
+        let slot_idx = self.slot_idx();
+        &self.shard.slots[slot_idx].as_ref().expect("indexed slot is occupied").timeline
+    }
+
+    /// Replace the occupied slot's timeline, returning the previous one.
+    pub fn insert(&mut self, timeline: Arc<Timeline>) -> Arc<Timeline> {
+        let slot_idx = self.slot_idx();
+        std::mem::replace(
+            &mut self.shard.slots[slot_idx].as_mut().expect("indexed slot is occupied").timeline,
+            timeline,
+        )
+    }
+
+    pub fn remove(mut self) -> Arc<Timeline> {
+        let slot_idx = self.shard.index.remove(&self.timeline_id).expect("occupied entry is indexed");
+        let slot = self.shard.slots[slot_idx].take().expect("indexed slot is occupied");
+        self.shard.free_list.push(slot_idx);
+        slot.timeline
+    }
+}
+
+pub struct VacantEntry<'a> {
+    shard: MutexGuard<'a, Shard>,
+    timeline_id: TimelineId,
+}
+
+impl<'a> VacantEntry<'a> {
+    pub fn insert(mut self, timeline: Arc<Timeline>) {
+        let slot_idx = self.shard.alloc(self.timeline_id, timeline);
+        self.shard.index.insert(self.timeline_id, slot_idx);
+    }
+}
@@ -1,48 +1,137 @@
-use std::{cmp::Ordering, collections::BinaryHeap};
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashSet},
+};
 
+/// Identifies the entity that scheduled an event, so that every timer it owns
+/// can be cancelled in one shot when the entity goes away.
+pub type OwnerId = u64;
 
+/// A stable handle to a scheduled event, returned by [`Timing::schedule`]. It
+/// stays valid after the event has been reordered inside the heap, and can be
+/// passed to [`Timing::cancel`] at any point before the event fires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerHandle(u32);
 
-pub struct Timing {
+pub struct Timing<T> {
     /// Current world's time.
     current_time: u64,
     /// Pending timers.
-    timers: BinaryHeap<Pending>,
+    timers: BinaryHeap<Pending<T>>,
     /// Global nonce.
     nonce: u32,
+    /// Nonces of events that have been cancelled but not yet discarded.
+    ///
+    /// `BinaryHeap` cannot remove an arbitrary interior element cheaply, so
+    /// cancellation is lazy: the nonce is remembered here and the matching
+    /// `Pending` is skipped when it reaches the top of the heap.
+    cancelled: HashSet<u32>,
 }
 
-impl Timing {
-    pub fn new() -> Timing {
+impl<T> Timing<T> {
+    pub fn new() -> Timing<T> {
         Timing {
             current_time: 0,
             timers: BinaryHeap::new(),
             nonce: 0,
+            cancelled: HashSet::new(),
         }
     }
 
-    /// Tick-tock the global clock. Return the event ready to be processed
-    /// or move the clock forward and then return the event.
-    pub fn step(&mut self) -> Option<Pending> {
-        if self.timers.len() == 0 {
-            // no future events
-            return None;
+    /// Schedule `payload` to fire at `time` and return a handle that can later
+    /// be passed to [`Timing::cancel`]. Not tied to any [`OwnerId`]; use
+    /// [`Timing::schedule_owned`] if the event should also be cancellable via
+    /// [`Timing::cancel_all`].
+    pub fn schedule(&mut self, time: u64, payload: T) -> TimerHandle {
+        self.push(time, None, None, payload)
+    }
+
+    /// Like [`Timing::schedule`], but tags the event with `owner` so a later
+    /// [`Timing::cancel_all`] for that owner will cancel it too.
+    pub fn schedule_owned(&mut self, time: u64, owner: OwnerId, payload: T) -> TimerHandle {
+        self.push(time, Some(owner), None, payload)
+    }
+
+    /// Push a fresh `Pending` onto the heap, assigning it a new nonce.
+    fn push(&mut self, time: u64, owner: Option<OwnerId>, period: Option<u64>, payload: T) -> TimerHandle {
+        self.nonce += 1;
+        let nonce = self.nonce;
+        self.timers.push(Pending {
+            time,
+            nonce,
+            owner,
+            period,
+            payload,
+        });
+        TimerHandle(nonce)
+    }
+
+    /// Cancel a single pending event by handle. Returns `true` if the event was
+    /// still pending (and is now cancelled), `false` if it had already fired or
+    /// been cancelled.
+    pub fn cancel(&mut self, handle: TimerHandle) -> bool {
+        // Only remember the nonce if it really refers to a live, not-yet-seen
+        // event, so `cancelled` can't grow without bound from bogus handles.
+        if handle.0 > self.nonce || self.cancelled.contains(&handle.0) {
+            return false;
         }
+        let inserted = self.cancelled.insert(handle.0);
+        self.maybe_compact();
+        inserted
+    }
 
-        if !self.is_event_ready() {
-            let next_time = self.timers.peek().unwrap().time;
-            println!("Advancing time from {} to {}", self.current_time, next_time);
-            self.current_time = next_time;
-            assert!(self.is_event_ready());
+    /// Cancel every pending event scheduled by `owner`. Returns the number of
+    /// events cancelled. Mirrors the `cancel`/`cancel_all` split used when a
+    /// gear is removed from an ECS physics world.
+    pub fn cancel_all(&mut self, owner: OwnerId) -> usize {
+        let mut cancelled = 0;
+        for pending in self.timers.iter() {
+            if pending.owner == Some(owner) && !self.cancelled.contains(&pending.nonce) {
+                cancelled += 1;
+            }
         }
+        // Collect first to avoid borrowing `timers` while mutating `cancelled`.
+        let nonces: Vec<u32> = self
+            .timers
+            .iter()
+            .filter(|p| p.owner == Some(owner))
+            .map(|p| p.nonce)
+            .collect();
+        for nonce in nonces {
+            self.cancelled.insert(nonce);
+        }
+        self.maybe_compact();
+        cancelled
+    }
 
-        self.timers.pop()
+    /// Rebuild the heap without the cancelled events once they make up a large
+    /// enough fraction of it, reclaiming the memory the lazy scheme leaks.
+    fn maybe_compact(&mut self) {
+        if self.cancelled.len() * 2 <= self.timers.len() {
+            return;
+        }
+        let cancelled = std::mem::take(&mut self.cancelled);
+        let kept = std::mem::take(&mut self.timers)
+            .into_vec()
+            .into_iter()
+            .filter(|p| !cancelled.contains(&p.nonce));
+        self.timers = BinaryHeap::from_iter(kept);
     }
 
-    /// TODO: write docs
-    pub fn schedule(&mut self, time: u64, event: Event) {
-        self.nonce += 1;
-        let nonce = self.nonce;
-        self.timers.push(Pending { time, nonce, event })
+    /// The timestamp of the earliest pending event, without popping it, so an
+    /// external loop can sleep exactly until the next wakeup instead of
+    /// busy-polling [`Timing::step`]. Skips lazily-cancelled events.
+    pub fn next_deadline(&self) -> Option<u64> {
+        self.timers
+            .iter()
+            .filter(|p| !self.cancelled.contains(&p.nonce))
+            .map(|p| p.time)
+            .min()
+    }
+
+    /// The current world time, as advanced by [`Timing::step`].
+    pub fn now(&self) -> u64 {
+        self.current_time
     }
 
     /// Return true if there is a ready event.
@@ -53,45 +142,186 @@ impl Timing {
     }
 }
 
-pub struct Pending {
-    pub time: u64,
-    pub nonce: u32,
-    pub event: Event,
+impl<T: Clone> Timing<T> {
+    /// Tick-tock the global clock. Move the clock forward to the next pending
+    /// event if needed, then pop it and return its fire time and payload.
+    /// Recurring events are re-armed before being returned.
+    pub fn step(&mut self) -> Option<(u64, T)> {
+        loop {
+            if self.timers.is_empty() {
+                // no future events
+                return None;
+            }
+
+            if !self.is_event_ready() {
+                let next_time = self.timers.peek().unwrap().time;
+                self.current_time = next_time;
+                assert!(self.is_event_ready());
+            }
+
+            let pending = self.timers.pop().unwrap();
+            if self.cancelled.remove(&pending.nonce) {
+                // This event was cancelled while it sat in the heap; drop it
+                // and look at the next one.
+                self.maybe_compact();
+                continue;
+            }
+            self.rearm(&pending);
+            return Some((pending.time, pending.payload));
+        }
+    }
+
+    /// Advance the clock to `target_time` and return, in fire order, every
+    /// event whose deadline falls at or before it. Events already due before
+    /// `current_time` are included too; the clock never moves backwards, so a
+    /// `target_time` in the past only drains the already-due events. Recurring
+    /// events are re-armed as they fire.
+    pub fn expire(&mut self, target_time: u64) -> Vec<(u64, T)> {
+        self.current_time = self.current_time.max(target_time);
+        let mut fired = Vec::new();
+        while self.is_event_ready() {
+            let pending = self.timers.pop().unwrap();
+            if self.cancelled.remove(&pending.nonce) {
+                self.maybe_compact();
+                continue;
+            }
+            self.rearm(&pending);
+            fired.push((pending.time, pending.payload));
+        }
+        fired
+    }
+
+    /// Schedule a recurring event: fire first at `first`, then every `interval`
+    /// ticks thereafter. A zero `interval` is meaningless for a repeat, so the
+    /// event degrades to a one-shot. Returns the handle of the first firing.
+    pub fn schedule_periodic(&mut self, first: u64, interval: u64, payload: T) -> TimerHandle {
+        let period = (interval > 0).then_some(interval);
+        self.push(first, None, period, payload)
+    }
+
+    /// Like [`Timing::schedule_periodic`], but tags every firing (including
+    /// re-armed ones) with `owner` so [`Timing::cancel_all`] can reach it.
+    pub fn schedule_periodic_owned(
+        &mut self,
+        first: u64,
+        interval: u64,
+        owner: OwnerId,
+        payload: T,
+    ) -> TimerHandle {
+        let period = (interval > 0).then_some(interval);
+        self.push(first, Some(owner), period, payload)
+    }
+
+    /// Re-insert a recurring event for its next firing. The next deadline is
+    /// normally `time + interval`, but if that has already slipped to at or
+    /// before `current_time` (a slow consumer), snap it to `current_time +
+    /// interval` so past-due repeats don't pile up into an ever-growing backlog.
+    fn rearm(&mut self, pending: &Pending<T>) {
+        let Some(interval) = pending.period else {
+            return;
+        };
+        let next = pending.time.saturating_add(interval);
+        let next = if next <= self.current_time {
+            self.current_time.saturating_add(interval)
+        } else {
+            next
+        };
+        self.push(next, pending.owner, pending.period, pending.payload.clone());
+    }
 }
 
-impl Pending {
-    pub fn process(&self) {
-        self.event.process();
+/// A boxed one-shot callback, invoked with the instant at which it fires.
+pub type Callback = Box<dyn FnOnce(u64)>;
+
+impl Timing<Callback> {
+    /// Register a callback to fire `duration` ticks from the current time, as in
+    /// `timing.add(duration, move |now| …)`. Returns a cancellable handle.
+    pub fn add(&mut self, duration: u64, callback: impl FnOnce(u64) + 'static) -> TimerHandle {
+        let time = self.current_time + duration;
+        self.schedule(time, Box::new(callback))
     }
+
+    /// Drain every callback whose deadline has already passed, invoking each one
+    /// with the current time. Callbacks fire in nondecreasing time order with
+    /// the nonce as tiebreaker, preserving scheduling order among equal
+    /// timestamps. This does not advance the clock; pair it with [`Timing::step`]
+    /// or [`Timing::expire`] to move time forward.
+    pub fn fire_ready(&mut self) {
+        while self.is_event_ready() {
+            let pending = self.timers.pop().unwrap();
+            if self.cancelled.remove(&pending.nonce) {
+                self.maybe_compact();
+                continue;
+            }
+            (pending.payload)(self.current_time);
+        }
+    }
+}
+
+/// A scheduled event. The `(time, nonce)` pair drives the heap ordering so the
+/// `payload` itself needs no ordering of its own, exactly like petgraph's
+/// `MinScored` wrapper that lets an unordered value ride along in a heap.
+pub struct Pending<T> {
+    pub time: u64,
+    pub nonce: u32,
+    pub owner: Option<OwnerId>,
+    /// Repeat interval for a recurring event, or `None` for a one-shot.
+    pub period: Option<u64>,
+    pub payload: T,
 }
 
 // BinaryHeap is a max-heap, and we want a min-heap. Reverse the ordering here
-// to get that.
-impl PartialOrd for Pending {
+// to get that. Only `(time, nonce)` participates so `T` is free to be any type.
+impl<T> PartialOrd for Pending<T> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        (other.time, other.nonce).partial_cmp(&(self.time, self.nonce))
+        Some(self.cmp(other))
     }
 }
 
-impl Ord for Pending {
+impl<T> Ord for Pending<T> {
     fn cmp(&self, other: &Self) -> Ordering {
         (other.time, other.nonce).cmp(&(self.time, self.nonce))
     }
 }
 
-impl PartialEq for Pending {
+impl<T> PartialEq for Pending<T> {
     fn eq(&self, other: &Self) -> bool {
-        &(other.time, other.nonce) == &(self.time, self.nonce)
+        (other.time, other.nonce) == (self.time, self.nonce)
     }
 }
 
-impl Eq for Pending {}
+impl<T> Eq for Pending<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancel_all_cancels_only_owned_events() {
+        let mut timing: Timing<&'static str> = Timing::new();
+        timing.schedule_owned(10, 1, "owner-1 a");
+        timing.schedule_owned(20, 1, "owner-1 b");
+        timing.schedule_owned(15, 2, "owner-2");
+        timing.schedule(5, "no owner");
+
+        assert_eq!(timing.cancel_all(1), 2);
+        // Already-cancelled events for owner 1 don't get double-counted.
+        assert_eq!(timing.cancel_all(1), 0);
+
+        let remaining: Vec<_> = std::iter::from_fn(|| timing.step()).collect();
+        assert_eq!(remaining, vec![(5, "no owner"), (15, "owner-2")]);
+    }
+
+    #[test]
+    fn schedule_periodic_owned_keeps_its_owner_across_rearm() {
+        let mut timing: Timing<&'static str> = Timing::new();
+        timing.schedule_periodic_owned(10, 10, 1, "tick");
 
-#[derive(Debug)]
-pub enum Event {}
+        // Fire it once so it gets rearmed for its next period.
+        assert_eq!(timing.step(), Some((10, "tick")));
 
-impl Event {
-    fn process(&self) {
-        // TODO:
+        // If the rearmed copy lost its owner tag, this would cancel nothing.
+        assert_eq!(timing.cancel_all(1), 1);
+        assert_eq!(timing.step(), None);
     }
 }